@@ -0,0 +1,143 @@
+use clap::Args;
+use ipnet::IpNet;
+use std::io::Write;
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::NetworkDisplay;
+use crate::rng::{HashRng, RandomRangeGenerator};
+
+#[derive(Args)]
+pub struct AllocateArgs {
+    /// Network to allocate subnets from
+    network: IpNet,
+    /// Prefix length of each allocated subnet
+    #[arg(short, long)]
+    split: u8,
+    /// Names to allocate a subnet for. When two keys hash to the same subnet,
+    /// the one listed first keeps it.
+    #[arg(short, long = "key", required = true)]
+    keys: Vec<String>,
+    /// Existing allocations that must not be handed out
+    #[arg(long, value_delimiter = ',')]
+    avoid: Vec<IpNet>,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &AllocateArgs) -> Result<()> {
+    let allocations: Vec<IpNet> = match args.network {
+        IpNet::V4(ipv4) => allocate(
+            &ipv4,
+            args.split,
+            &args.keys,
+            &family(&args.avoid, |net| match net {
+                IpNet::V4(net) => Some(*net),
+                _ => None,
+            }),
+        )?
+        .into_iter()
+        .map(IpNet::V4)
+        .collect(),
+        IpNet::V6(ipv6) => allocate(
+            &ipv6,
+            args.split,
+            &args.keys,
+            &family(&args.avoid, |net| match net {
+                IpNet::V6(net) => Some(*net),
+                _ => None,
+            }),
+        )?
+        .into_iter()
+        .map(IpNet::V6)
+        .collect(),
+    };
+
+    let version = match args.network {
+        IpNet::V4(_) => "ipv4",
+        IpNet::V6(_) => "ipv6",
+    };
+    let width = args.keys.iter().map(String::len).max().unwrap_or(0);
+
+    ctx.writeln(format!("-[{version} : {}] - 0\n", args.network))?;
+    ctx.writeln("[Allocations]".to_string())?;
+
+    for (key, allocation) in args.keys.iter().zip(allocations) {
+        ctx.writeln(format!("{key:<width$} - {allocation}"))?;
+    }
+
+    Ok(())
+}
+
+fn family<N>(nets: &[IpNet], select: impl Fn(&IpNet) -> Option<N>) -> Vec<N> {
+    nets.iter().filter_map(select).collect()
+}
+
+/// Hashes each key to a subnet of `network`, in order, so that no two overlap.
+pub fn allocate<N>(network: &N, split: u8, keys: &[String], avoid: &[N]) -> Result<Vec<N>>
+where
+    N: NetworkDisplay + Copy,
+    HashRng: RandomRangeGenerator<N::Primitive>,
+{
+    let mut used = avoid.to_vec();
+    let mut allocations = Vec::with_capacity(keys.len());
+
+    for (i, key) in keys.iter().enumerate() {
+        if keys[..i].contains(key) {
+            return Err(Error::DuplicateKey(key.clone()));
+        }
+
+        let mut rng = HashRng::new(key);
+        let allocation = network.generate_random_splits(split, 1, &used, &mut rng)?[0];
+
+        used.push(allocation);
+        allocations.push(allocation);
+    }
+
+    Ok(allocations)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use ipnet::Ipv4Net;
+
+    use pretty_assertions::assert_eq;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn allocations_are_stable_per_key() {
+        let network = Ipv4Net::from_str("10.0.0.0/16").unwrap();
+
+        let first = allocate(&network, 24, &keys(&["team-payments"]), &[]).unwrap();
+        let second = allocate(&network, 24, &keys(&["team-search", "team-payments"]), &[]).unwrap();
+
+        assert_eq!(first[0].to_string(), "10.0.0.0/24");
+        assert_eq!(second[1], first[0]);
+    }
+
+    #[test]
+    fn colliding_keys_get_distinct_subnets() {
+        let network = Ipv4Net::from_str("10.0.0.0/30").unwrap();
+
+        let allocations = allocate(&network, 31, &keys(&["a", "b"]), &[]).unwrap();
+
+        assert_ne!(allocations[0], allocations[1]);
+
+        let e = allocate(&network, 31, &keys(&["a", "b", "c"]), &[]).unwrap_err();
+
+        assert!(matches!(e, Error::SpaceExhausted(31, _)));
+    }
+
+    #[test]
+    fn rejects_duplicate_keys() {
+        let network = Ipv4Net::from_str("10.0.0.0/16").unwrap();
+
+        let e = allocate(&network, 24, &keys(&["a", "a"]), &[]).unwrap_err();
+
+        assert!(matches!(e, Error::DuplicateKey(_)));
+    }
+}
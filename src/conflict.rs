@@ -0,0 +1,287 @@
+use clap::Args;
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::fmt;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::ifaddr;
+use crate::parse::parse_network;
+
+/// The RTF_UP route flag; routes without it are ignored by the kernel.
+const RTF_UP: u32 = 0x0001;
+
+#[derive(Args)]
+pub struct CheckConflictArgs {
+    /// Networks about to be created
+    #[arg(required = true, value_parser = parse_network)]
+    networks: Vec<IpNet>,
+    /// IPv4 routing table to read
+    #[arg(long, default_value = "/proc/net/route")]
+    route_file: PathBuf,
+    /// IPv6 routing table to read
+    #[arg(long, default_value = "/proc/net/ipv6_route")]
+    ipv6_route_file: PathBuf,
+    /// Only check routes, not the addresses of local interfaces
+    #[arg(long)]
+    no_interfaces: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Source {
+    Route,
+    Address,
+}
+
+/// A network already in use on this host.
+#[derive(Debug, PartialEq, Eq)]
+struct LocalNetwork {
+    source: Source,
+    interface: String,
+    network: IpNet,
+}
+
+impl fmt::Display for LocalNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source {
+            Source::Route => write!(f, "route   - {} dev {}", self.network, self.interface),
+            Source::Address => write!(f, "address - {} on {}", self.network, self.interface),
+        }
+    }
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &CheckConflictArgs) -> Result<()> {
+    let mut local = parse_ipv4_routes(&read_table(&args.route_file)?)?;
+    local.extend(parse_ipv6_routes(&read_table(&args.ipv6_route_file)?)?);
+
+    if !args.no_interfaces {
+        local.extend(
+            ifaddr::local_networks()?
+                .into_iter()
+                .map(|(interface, network)| LocalNetwork {
+                    source: Source::Address,
+                    interface,
+                    network,
+                }),
+        );
+    }
+
+    for (i, network) in args.networks.iter().enumerate() {
+        let network = network.trunc();
+        let conflicts: Vec<&LocalNetwork> = local
+            .iter()
+            .filter(|local| overlaps(&network, &local.network))
+            .collect();
+
+        if i > 0 {
+            ctx.writeln("\n-")?;
+        }
+
+        let version = match network {
+            IpNet::V4(_) => "ipv4",
+            IpNet::V6(_) => "ipv6",
+        };
+
+        ctx.writeln(format!("-[{version} : {network}] - 0\n"))?;
+        ctx.writeln("[Conflicts]".to_string())?;
+
+        if conflicts.is_empty() {
+            ctx.writeln("None")?;
+
+            continue;
+        }
+
+        for conflict in &conflicts {
+            ctx.writeln(conflict)?;
+        }
+
+        ctx.error_without_exit(format!(
+            "{network} overlaps {} local route(s) or address(es)",
+            conflicts.len()
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Local addresses keep their host bits, so compare the networks they sit in.
+fn overlaps(network: &IpNet, local: &IpNet) -> bool {
+    let local = local.trunc();
+
+    network.contains(&local) || local.contains(network)
+}
+
+/// Reads a routing table, treating a missing one (e.g. IPv6 disabled) as empty.
+fn read_table(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn hex<T: TryFrom<u128>>(field: Option<&str>, line: &str) -> Result<T> {
+    field
+        .and_then(|field| u128::from_str_radix(field, 16).ok())
+        .and_then(|value| T::try_from(value).ok())
+        .ok_or_else(|| Error::RouteParse(line.to_string()))
+}
+
+/// Parses `/proc/net/route`, skipping the header, default routes and routes
+/// that aren't up. Addresses are printed in host byte order.
+fn parse_ipv4_routes(contents: &str) -> Result<Vec<LocalNetwork>> {
+    let mut routes = Vec::new();
+
+    for line in contents
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+    {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let destination: u32 = hex(fields.get(1).copied(), line)?;
+        let flags: u32 = hex(fields.get(3).copied(), line)?;
+        let mask: u32 = hex(fields.get(7).copied(), line)?;
+
+        if flags & RTF_UP == 0 || mask == 0 {
+            continue;
+        }
+
+        let network = Ipv4Net::with_netmask(
+            Ipv4Addr::from(destination.to_ne_bytes()),
+            Ipv4Addr::from(mask.to_ne_bytes()),
+        )
+        .map_err(|_| Error::RouteParse(line.to_string()))?;
+
+        routes.push(LocalNetwork {
+            source: Source::Route,
+            interface: fields[0].to_string(),
+            network: IpNet::V4(network),
+        });
+    }
+
+    Ok(routes)
+}
+
+/// Parses `/proc/net/ipv6_route`, which has no header and prints addresses as
+/// 32 hex digits in network byte order.
+fn parse_ipv6_routes(contents: &str) -> Result<Vec<LocalNetwork>> {
+    let mut routes = Vec::new();
+
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let destination: u128 = hex(fields.first().copied(), line)?;
+        let prefix_len: u8 = hex(fields.get(1).copied(), line)?;
+        let flags: u32 = hex(fields.get(8).copied(), line)?;
+        let Some(interface) = fields.get(9) else {
+            return Err(Error::RouteParse(line.to_string()));
+        };
+
+        if flags & RTF_UP == 0 || prefix_len == 0 {
+            continue;
+        }
+
+        let network = Ipv6Net::new(Ipv6Addr::from(destination), prefix_len)
+            .map_err(|_| Error::RouteParse(line.to_string()))?;
+
+        routes.push(LocalNetwork {
+            source: Source::Route,
+            interface: interface.to_string(),
+            network: IpNet::V6(network),
+        });
+    }
+
+    Ok(routes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+
+    use pretty_assertions::assert_eq;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+    fn route(interface: &str, network: &str) -> LocalNetwork {
+        LocalNetwork {
+            source: Source::Route,
+            interface: interface.to_string(),
+            network: IpNet::from_str(network).unwrap(),
+        }
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn parses_ipv4_routes() {
+        let routes = parse_ipv4_routes(include_str!("../tests/fixtures/proc_net_route")).unwrap();
+
+        assert_eq!(
+            routes,
+            [
+                route("eth0", "192.168.1.0/24"),
+                route("docker0", "172.17.0.0/16"),
+                route("wg0", "10.8.0.0/24"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_ipv6_routes() {
+        let routes =
+            parse_ipv6_routes(include_str!("../tests/fixtures/proc_net_ipv6_route")).unwrap();
+
+        assert_eq!(
+            routes,
+            [
+                route("eth0", "2001:db8:1::/64"),
+                route("eth0", "fe80::/64"),
+                route("lo", "::1/128"),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_routes() {
+        let e = parse_ipv6_routes("not a route").unwrap_err();
+
+        assert!(matches!(e, Error::RouteParse(_)));
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn reports_overlapping_routes() {
+        let mut ctx = create_test_ctx();
+        let args = CheckConflictArgs {
+            networks: vec![
+                IpNet::from_str("172.16.0.0/12").unwrap(),
+                IpNet::from_str("10.9.0.0/16").unwrap(),
+            ],
+            route_file: Path::new(FIXTURES).join("proc_net_route"),
+            ipv6_route_file: Path::new(FIXTURES).join("proc_net_ipv6_route"),
+            no_interfaces: true,
+        };
+
+        run(&mut ctx, &args).unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "-[ipv4 : 172.16.0.0/12] - 0
+
+[Conflicts]
+route   - 172.17.0.0/16 dev docker0
+
+-
+-[ipv4 : 10.9.0.0/16] - 0
+
+[Conflicts]
+None
+"
+        );
+        assert!(ctx.errored);
+    }
+}
@@ -42,6 +42,11 @@ impl<W: Write, E: Write> Ctx<W, E> {
     pub fn output(&self) -> &W {
         &self.output
     }
+
+    #[cfg(test)]
+    pub fn err_output(&self) -> &E {
+        &self.err_output
+    }
 }
 
 #[cfg(test)]
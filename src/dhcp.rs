@@ -0,0 +1,350 @@
+use clap::{Args, ValueEnum};
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::fmt;
+use std::io::Write;
+use std::net::Ipv4Addr;
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::NetworkCore;
+use crate::parse::parse_network;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum DhcpFormat {
+    Isc,
+    Kea,
+    Dnsmasq,
+}
+
+#[derive(Args)]
+pub struct DhcpArgs {
+    /// Network to serve
+    #[arg(value_parser = parse_network)]
+    network: IpNet,
+    /// Kind of server configuration to emit
+    #[arg(short, long, value_enum)]
+    format: DhcpFormat,
+    /// Emit one stanza per subnet of this prefix length
+    #[arg(short, long)]
+    split: Option<u8>,
+    /// Addresses to keep out of the pool after the router
+    #[arg(long, default_value_t = 0)]
+    reserve_start: u64,
+    /// Addresses to keep out of the pool at the end of the range
+    #[arg(long, default_value_t = 0)]
+    reserve_end: u64,
+}
+
+/// A subnet and the dynamic range handed out in it.
+struct Pool<N: NetworkCore> {
+    network: N,
+    start: N::Address,
+    end: N::Address,
+}
+
+impl Pool<Ipv4Net> {
+    /// The first usable address, which is kept out of the pool.
+    fn router(&self) -> Ipv4Addr {
+        self.network
+            .usable_hosts()
+            .next()
+            .unwrap_or(self.network.network())
+    }
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &DhcpArgs) -> Result<()> {
+    let lines = match args.network {
+        IpNet::V4(ipv4) => {
+            let pools = pools(&ipv4.trunc(), args)?;
+
+            match args.format {
+                DhcpFormat::Isc => isc_v4(&pools),
+                DhcpFormat::Kea => kea_v4(&pools),
+                DhcpFormat::Dnsmasq => dnsmasq_v4(&pools),
+            }
+        }
+        IpNet::V6(ipv6) => {
+            let pools = pools(&ipv6.trunc(), args)?;
+
+            match args.format {
+                DhcpFormat::Isc => isc_v6(&pools),
+                DhcpFormat::Kea => kea_v6(&pools),
+                DhcpFormat::Dnsmasq => dnsmasq_v6(&pools),
+            }
+        }
+    };
+
+    for line in lines {
+        ctx.writeln(line)?;
+    }
+
+    Ok(())
+}
+
+fn pools<N: NetworkCore + fmt::Display + Copy>(
+    network: &N,
+    args: &DhcpArgs,
+) -> Result<Vec<Pool<N>>> {
+    let networks: Vec<N> = match args.split {
+        Some(split) => network
+            .subnets(split)
+            .map_err(|_| Error::SplitSmallerThanPrefixLen(split, network.prefix_len()))?
+            .collect(),
+        None => vec![*network],
+    };
+
+    networks
+        .into_iter()
+        .map(|network| pool(network, args.reserve_start, args.reserve_end))
+        .collect()
+}
+
+/// Usable hosts minus the first one (the IPv4 router, or the IPv6 Subnet-Router
+/// anycast address) and the reservations at either end.
+fn pool<N: NetworkCore + fmt::Display>(
+    network: N,
+    reserve_start: u64,
+    reserve_end: u64,
+) -> Result<Pool<N>> {
+    let hosts = network
+        .usable_hosts()
+        .skip_hosts(reserve_start.saturating_add(1));
+    let end = i128::from(reserve_end).checked_add(1).map(|n| -n);
+
+    match (hosts.nth_host(0), end.and_then(|end| hosts.nth_host(end))) {
+        (Some(start), Some(end)) => Ok(Pool {
+            network,
+            start,
+            end,
+        }),
+        _ => Err(Error::EmptyPool(network.to_string())),
+    }
+}
+
+fn isc_v4(pools: &[Pool<Ipv4Net>]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for pool in pools {
+        let network = &pool.network;
+
+        lines.push(format!(
+            "subnet {} netmask {} {{",
+            network.network(),
+            network.netmask()
+        ));
+        lines.push(format!("  range {} {};", pool.start, pool.end));
+        lines.push(format!("  option routers {};", pool.router()));
+        lines.push(format!("  option subnet-mask {};", network.netmask()));
+        lines.push(format!(
+            "  option broadcast-address {};",
+            network.broadcast()
+        ));
+        lines.push("}".to_string());
+    }
+
+    lines
+}
+
+fn isc_v6(pools: &[Pool<Ipv6Net>]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for pool in pools {
+        lines.push(format!("subnet6 {} {{", pool.network));
+        lines.push(format!("  range6 {} {};", pool.start, pool.end));
+        lines.push("}".to_string());
+    }
+
+    lines
+}
+
+/// Kea takes JSON; each subnet gets a numeric id starting at 1.
+fn kea<N: NetworkCore + fmt::Display>(
+    key: &str,
+    pools: &[Pool<N>],
+    options: impl Fn(&Pool<N>) -> Vec<(&'static str, String)>,
+) -> Vec<String> {
+    let mut lines = vec![format!("\"{key}\": [")];
+
+    for (i, pool) in pools.iter().enumerate() {
+        let options = options(pool);
+
+        lines.push("  {".to_string());
+        lines.push(format!("    \"id\": {},", i + 1));
+        lines.push(format!("    \"subnet\": \"{}\",", pool.network));
+        lines.push(format!(
+            "    \"pools\": [ {{ \"pool\": \"{} - {}\" }} ]{}",
+            pool.start,
+            pool.end,
+            if options.is_empty() { "" } else { "," }
+        ));
+
+        if !options.is_empty() {
+            lines.push("    \"option-data\": [".to_string());
+
+            for (j, (name, data)) in options.iter().enumerate() {
+                let comma = if j + 1 < options.len() { "," } else { "" };
+
+                lines.push(format!(
+                    "      {{ \"name\": \"{name}\", \"data\": \"{data}\" }}{comma}"
+                ));
+            }
+
+            lines.push("    ]".to_string());
+        }
+
+        let comma = if i + 1 < pools.len() { "," } else { "" };
+        lines.push(format!("  }}{comma}"));
+    }
+
+    lines.push("]".to_string());
+
+    lines
+}
+
+fn kea_v4(pools: &[Pool<Ipv4Net>]) -> Vec<String> {
+    kea("subnet4", pools, |pool| {
+        vec![
+            ("routers", pool.router().to_string()),
+            ("subnet-mask", pool.network.netmask().to_string()),
+            ("broadcast-address", pool.network.broadcast().to_string()),
+        ]
+    })
+}
+
+fn kea_v6(pools: &[Pool<Ipv6Net>]) -> Vec<String> {
+    kea("subnet6", pools, |_| Vec::new())
+}
+
+/// Tags ranges and options when there is more than one subnet, so each
+/// subnet's options only apply to its own clients.
+fn dnsmasq_tag(pools: usize, i: usize, prefix: &str) -> String {
+    if pools > 1 {
+        format!("{prefix}:net{i},")
+    } else {
+        String::new()
+    }
+}
+
+fn dnsmasq_v4(pools: &[Pool<Ipv4Net>]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for (i, pool) in pools.iter().enumerate() {
+        let network = &pool.network;
+
+        lines.push(format!(
+            "dhcp-range={}{},{},{},{}",
+            dnsmasq_tag(pools.len(), i, "set"),
+            pool.start,
+            pool.end,
+            network.netmask(),
+            network.broadcast()
+        ));
+        lines.push(format!(
+            "dhcp-option={}option:router,{}",
+            dnsmasq_tag(pools.len(), i, "tag"),
+            pool.router()
+        ));
+    }
+
+    lines
+}
+
+fn dnsmasq_v6(pools: &[Pool<Ipv6Net>]) -> Vec<String> {
+    pools
+        .iter()
+        .enumerate()
+        .map(|(i, pool)| {
+            format!(
+                "dhcp-range={}{},{},{}",
+                dnsmasq_tag(pools.len(), i, "set"),
+                pool.start,
+                pool.end,
+                pool.network.prefix_len()
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn args(reserve_start: u64, reserve_end: u64) -> DhcpArgs {
+        DhcpArgs {
+            network: IpNet::from_str("192.168.1.0/24").unwrap(),
+            format: DhcpFormat::Isc,
+            split: None,
+            reserve_start,
+            reserve_end,
+        }
+    }
+
+    #[test]
+    fn renders_isc_subnet_with_reservations() {
+        let network = Ipv4Net::from_str("192.168.1.0/24").unwrap();
+        let pools = pools(&network, &args(9, 4)).unwrap();
+
+        assert_eq!(
+            isc_v4(&pools),
+            [
+                "subnet 192.168.1.0 netmask 255.255.255.0 {",
+                "  range 192.168.1.11 192.168.1.250;",
+                "  option routers 192.168.1.1;",
+                "  option subnet-mask 255.255.255.0;",
+                "  option broadcast-address 192.168.1.255;",
+                "}",
+            ]
+        );
+    }
+
+    #[test]
+    fn tags_dnsmasq_ranges_per_split() {
+        let network = Ipv4Net::from_str("10.0.0.0/24").unwrap();
+        let args = DhcpArgs {
+            split: Some(25),
+            ..args(0, 0)
+        };
+
+        assert_eq!(
+            dnsmasq_v4(&pools(&network, &args).unwrap()),
+            [
+                "dhcp-range=set:net0,10.0.0.2,10.0.0.126,255.255.255.128,10.0.0.127",
+                "dhcp-option=tag:net0,option:router,10.0.0.1",
+                "dhcp-range=set:net1,10.0.0.130,10.0.0.254,255.255.255.128,10.0.0.255",
+                "dhcp-option=tag:net1,option:router,10.0.0.129",
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_kea_dhcpv6_pool() {
+        let network = Ipv6Net::from_str("2001:db8::/120").unwrap();
+
+        assert_eq!(
+            kea_v6(&pools(&network, &args(0, 1)).unwrap()),
+            [
+                "\"subnet6\": [",
+                "  {",
+                "    \"id\": 1,",
+                "    \"subnet\": \"2001:db8::/120\",",
+                "    \"pools\": [ { \"pool\": \"2001:db8::1 - 2001:db8::fe\" } ]",
+                "  }",
+                "]",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_empty_pools() {
+        let network = Ipv4Net::from_str("10.0.0.0/30").unwrap();
+
+        assert!(matches!(
+            pools(&network, &args(1, 0)),
+            Err(Error::EmptyPool(_))
+        ));
+    }
+}
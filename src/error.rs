@@ -6,12 +6,68 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("Failed to parse `{0}` as interface")]
     AddrParse(String),
+    #[error("No IP subnet supplied")]
+    NoInput,
+    #[error("--random requires --split or --hosts")]
+    RandomWithoutSplit,
     #[error("Prefix length error: `{0}`")]
     PrefixLen(#[from] ipnet::PrefixLenError),
     #[error("Split mask ({0}) must be greater than the input prefix length ({1})")]
     SplitSmallerThanPrefixLen(u8, u8),
     #[error("Split mask cannot be greater than {0}. Supplied: {1}")]
     SplitTooBig(u8, u8),
+    #[error("Moving {0} block(s) forward from {1} leaves the address space")]
+    NextOutOfRange(u64, String),
+    #[error("Moving {0} block(s) back from {1} leaves the address space")]
+    PrevOutOfRange(u64, String),
+    #[error("{0} spans the whole address space and has no parent")]
+    NoParent(String),
+    #[error("Supernet mask ({0}) must not be greater than the input prefix length ({1})")]
+    SupernetLargerThanPrefixLen(u8, u8),
+    #[error("Host index {0} is outside the usable range of {1}")]
+    HostIndexOutOfRange(i128, String),
+    #[error("No unused host addresses left in {0}")]
+    NoFreeHosts(String),
+    #[error("{0} has no usable addresses left after the {1} reservations")]
+    NoRoomForReservations(String, &'static str),
+    #[error("No free /{0} subnets left in {1}")]
+    SpaceExhausted(u8, String),
+    #[error("Failed to parse `{0}` as a MAC address or EUI-64")]
+    MacParse(String),
+    #[error("Key `{0}` was supplied more than once")]
+    DuplicateKey(String),
+    #[error("{0} is not inside {1}")]
+    NotInNetwork(String, String),
+    #[error("Only one IPv4 and one IPv6 network may be supplied, got another: {0}")]
+    DuplicateFamily(String),
+    #[error("`{0}` is already in use")]
+    NameInUse(String),
+    #[error("No pool or allocation named `{0}`")]
+    UnknownName(String),
+    #[error("{0} overlaps the pool `{1}`")]
+    PoolOverlap(String, String),
+    #[error("--pool is required when there is not exactly one pool")]
+    PoolRequired,
+    #[error("Database error: `{0}`")]
+    Json(#[from] serde_json::Error),
+    #[error("Failed to parse route `{0}`")]
+    RouteParse(String),
+    #[error("Invalid cluster CIDRs: {0}")]
+    InvalidClusterCidrs(String),
+    #[error("No addresses left for a DHCP pool in {0}")]
+    EmptyPool(String),
+    #[error("Unbalanced quotes in `{0}`")]
+    UnbalancedQuotes(String),
+    #[error("Failed to open the terminal: `{0}`")]
+    Terminal(std::io::Error),
+    #[error("Line editor error: `{0}`")]
+    Readline(#[from] rustyline::error::ReadlineError),
+    #[error("There is no previous result for `$_` yet")]
+    NoPreviousResult,
+    #[error("Unbalanced braces in template `{0}`")]
+    TemplateParse(String),
+    #[error("Unknown template field `{0}`")]
+    UnknownTemplateField(String),
 }
 
 /// Type alias for the standard [`Result`] type.
@@ -0,0 +1,341 @@
+use clap::Args;
+use ipnet::IpNet;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use crate::context::Ctx;
+use crate::error::Result;
+use crate::parse::parse_any_notation;
+
+#[derive(Args)]
+pub struct ExtractArgs {
+    /// Files to scan, reading stdin when none are given or for `-`
+    files: Vec<PathBuf>,
+    /// Show how often each address or network was seen, most frequent first
+    #[arg(short, long)]
+    count: bool,
+    /// Count IPv4 matches by the covering network of this prefix length
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(0..=32))]
+    group: Option<u8>,
+    /// Prefix length IPv6 matches are grouped by when --group is given
+    #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u8).range(0..=128))]
+    group_v6: u8,
+    /// Only show the N most frequent results, with their counts
+    #[arg(short = 'n', long)]
+    top: Option<usize>,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &ExtractArgs) -> Result<()> {
+    let mut found = Vec::new();
+
+    if args.files.is_empty() {
+        found.extend(extract(io::stdin().lock())?);
+    }
+
+    for path in &args.files {
+        if path.as_os_str() == "-" {
+            found.extend(extract(io::stdin().lock())?);
+        } else {
+            found.extend(extract(BufReader::new(File::open(path)?))?);
+        }
+    }
+
+    report(ctx, found, args)
+}
+
+/// Writes the matches deduplicated in address order, or with their counts
+/// most frequent first when counting, grouping or limiting to the top N.
+fn report<W: Write, E: Write>(
+    ctx: &mut Ctx<W, E>,
+    mut found: Vec<IpNet>,
+    args: &ExtractArgs,
+) -> Result<()> {
+    let counting = args.count || args.group.is_some() || args.top.is_some();
+
+    if let Some(group) = args.group {
+        found = found
+            .iter()
+            .map(|net| {
+                let prefix_len = match net {
+                    IpNet::V4(_) => group,
+                    IpNet::V6(_) => args.group_v6,
+                };
+
+                Ok(IpNet::new(net.addr(), prefix_len.min(net.prefix_len()))?.trunc())
+            })
+            .collect::<Result<_>>()?;
+    }
+
+    let mut counts: BTreeMap<IpNet, usize> = BTreeMap::new();
+
+    for net in found {
+        *counts.entry(net).or_default() += 1;
+    }
+
+    let mut results: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(net, count)| (display(&net, args.group.is_some()), count))
+        .collect();
+
+    if counting {
+        // Stable, so equal counts stay in address order
+        results.sort_by_key(|(_, count)| Reverse(*count));
+    }
+
+    let width = results.iter().map(|(net, _)| net.len()).max().unwrap_or(0);
+
+    for (net, count) in results.into_iter().take(args.top.unwrap_or(usize::MAX)) {
+        if counting {
+            ctx.writeln(format!("{net:<width$} - {count}"))?;
+        } else {
+            ctx.writeln(net)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Shows single addresses without their prefix length, unless grouped.
+fn display(net: &IpNet, grouped: bool) -> String {
+    if !grouped && net.prefix_len() == net.max_prefix_len() {
+        net.addr().to_string()
+    } else {
+        net.to_string()
+    }
+}
+
+/// Scans text for addresses and CIDRs in the order they appear, tolerating
+/// invalid UTF-8 as logs often contain it.
+pub fn extract(mut reader: impl BufRead) -> Result<Vec<IpNet>> {
+    let mut found = Vec::new();
+    let mut buf = Vec::new();
+
+    while reader.read_until(b'\n', &mut buf)? > 0 {
+        found.extend(scan(&String::from_utf8_lossy(&buf)));
+        buf.clear();
+    }
+
+    Ok(found)
+}
+
+fn is_address_char(c: char) -> bool {
+    c.is_ascii_hexdigit() || matches!(c, ':' | '.')
+}
+
+fn is_word_char(c: Option<char>) -> bool {
+    c.is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Length of a `/prefix` at the start of `rest`, if there is one.
+fn prefix_len_suffix(rest: &str) -> usize {
+    let Some(digits) = rest.strip_prefix('/') else {
+        return 0;
+    };
+    let len = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+
+    if (1..=3).contains(&len) {
+        len + 1
+    } else {
+        0
+    }
+}
+
+/// Whether a token has the shape of a dotted quad or an IPv6 address, so
+/// bare numbers and version strings aren't read as legacy IPv4 notation.
+fn looks_like_address(token: &str) -> bool {
+    let address = token.split_once('/').map_or(token, |(address, _)| address);
+
+    if address.contains(':') {
+        address.matches(':').count() >= 2
+    } else {
+        let parts: Vec<&str> = address.split('.').collect();
+
+        parts.len() == 4
+            && parts
+                .iter()
+                .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+    }
+}
+
+fn parse_candidate(token: &str) -> Option<IpNet> {
+    if looks_like_address(token) {
+        if let Ok(net) = parse_any_notation(token) {
+            return Some(net);
+        }
+    }
+
+    // Retry without a trailing `:port`
+    let (address, _) = token.rsplit_once(':')?;
+
+    (looks_like_address(address) && !address.ends_with(':'))
+        .then(|| parse_any_notation(address).ok())
+        .flatten()
+}
+
+fn scan(line: &str) -> Vec<IpNet> {
+    let mut found = Vec::new();
+    let mut rest = line;
+
+    while let Some(mut start) = rest.find(is_address_char) {
+        let mut end = rest[start..]
+            .find(|c| !is_address_char(c))
+            .map_or(rest.len(), |len| start + len);
+        end += prefix_len_suffix(&rest[end..]);
+
+        // A single colon ends a label, e.g. `http://` or `src:`, unlike the
+        // `::` that can start an IPv6 address
+        if let Some(colon) = rest[start..end].find(':') {
+            let label_ends = !rest[start + colon..].starts_with("::");
+
+            if label_ends && (colon == 0 || is_word_char(rest[..start].chars().next_back())) {
+                start += colon + 1;
+            }
+        }
+
+        // Skip tokens that are part of a longer word, e.g. `cafe` in `cafeteria`
+        if !is_word_char(rest[..start].chars().next_back())
+            && !is_word_char(rest[end..].chars().next())
+        {
+            let token = rest[start..end].trim_end_matches('.');
+
+            if let Some(net) = parse_candidate(token) {
+                found.push(net);
+            }
+        }
+
+        rest = &rest[end..];
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+
+    use pretty_assertions::assert_eq;
+
+    const LOG: &str = "\
+10.0.0.1 10.0.0.2 10.0.0.1 10.1.0.1
+2001:db8::1 2001:db8::2 2001:db8:0:1::1 10.0.0.2 10.0.0.1
+";
+
+    fn args() -> ExtractArgs {
+        ExtractArgs {
+            files: Vec::new(),
+            count: false,
+            group: None,
+            group_v6: 64,
+            top: None,
+        }
+    }
+
+    fn report_log(args: &ExtractArgs) -> String {
+        let mut ctx = create_test_ctx();
+
+        report(&mut ctx, extract(LOG.as_bytes()).unwrap(), args).unwrap();
+
+        get_output_as_string(&ctx)
+    }
+
+    fn nets(nets: &[&str]) -> Vec<IpNet> {
+        nets.iter()
+            .map(|net| IpNet::from_str(net).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn finds_addresses_and_networks_in_log_lines() {
+        let log = "\
+Oct 18 12:34:56 web1 sshd[42]: Failed password from 203.0.113.9 port 52113
+client 198.51.100.7:443 -> [2001:db8::1]:8443 (route 10.0.0.0/8).
+v1.2.3.4 build 1.2.3, see std::io and fe80::1%eth0
+GET http://10.0.0.1/api src:10.0.0.2
+https://192.0.2.5:8080/x and http://[2001:db8::2]/
+";
+
+        assert_eq!(
+            extract(log.as_bytes()).unwrap(),
+            nets(&[
+                "203.0.113.9/32",
+                "198.51.100.7/32",
+                "2001:db8::1/128",
+                "10.0.0.0/8",
+                "fe80::1/128",
+                "10.0.0.1/32",
+                "10.0.0.2/32",
+                "192.0.2.5/32",
+                "2001:db8::2/128",
+            ])
+        );
+    }
+
+    #[test]
+    fn uses_the_same_notation_rules_as_parse_ip() {
+        assert_eq!(
+            extract("from 010.0.0.1 and 999.1.1.1".as_bytes()).unwrap(),
+            nets(&["8.0.0.1/32"])
+        );
+    }
+
+    #[test]
+    fn lists_unique_matches_in_address_order() {
+        assert_eq!(
+            report_log(&args()),
+            "10.0.0.1\n10.0.0.2\n10.1.0.1\n2001:db8::1\n2001:db8::2\n2001:db8:0:1::1\n"
+        );
+    }
+
+    #[test]
+    fn counts_most_frequent_first() {
+        assert_eq!(
+            report_log(&ExtractArgs {
+                count: true,
+                ..args()
+            }),
+            "\
+10.0.0.1        - 3
+10.0.0.2        - 2
+10.1.0.1        - 1
+2001:db8::1     - 1
+2001:db8::2     - 1
+2001:db8:0:1::1 - 1
+"
+        );
+    }
+
+    #[test]
+    fn groups_by_prefix_length_per_family() {
+        assert_eq!(
+            report_log(&ExtractArgs {
+                group: Some(16),
+                group_v6: 48,
+                ..args()
+            }),
+            "\
+10.0.0.0/16   - 5
+2001:db8::/48 - 3
+10.1.0.0/16   - 1
+"
+        );
+    }
+
+    #[test]
+    fn top_implies_counting() {
+        assert_eq!(
+            report_log(&ExtractArgs {
+                top: Some(2),
+                ..args()
+            }),
+            "10.0.0.1        - 3\n10.0.0.2        - 2\n"
+        );
+    }
+}
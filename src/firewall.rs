@@ -0,0 +1,287 @@
+use clap::{Args, ValueEnum};
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::io::Write;
+
+use crate::context::Ctx;
+use crate::error::Result;
+use crate::parse::parse_network;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FirewallFormat {
+    Nftables,
+    Iptables,
+    Ipset,
+    Cisco,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Action {
+    Accept,
+    #[default]
+    Drop,
+}
+
+#[derive(Args)]
+pub struct FirewallArgs {
+    /// Prefixes to match
+    #[arg(required = true, value_parser = parse_network)]
+    prefixes: Vec<IpNet>,
+    /// Kind of firewall fragment to emit
+    #[arg(short, long, value_enum)]
+    format: FirewallFormat,
+    /// Action for matching traffic
+    #[arg(short, long, value_enum, default_value_t)]
+    action: Action,
+    /// Name of the sets or ACLs, which get a `_v4`/`_v6` suffix
+    #[arg(short, long, default_value = "iprs")]
+    name: String,
+    /// Chain to add rules to (defaults to `input`, or `INPUT` for iptables)
+    #[arg(long)]
+    chain: Option<String>,
+    /// nftables family and table holding the sets and rules
+    #[arg(long, default_value = "inet filter")]
+    table: String,
+    /// Merge adjacent and overlapping prefixes first
+    #[arg(long)]
+    aggregate: bool,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &FirewallArgs) -> Result<()> {
+    let prefixes = if args.aggregate {
+        IpNet::aggregate(&args.prefixes)
+    } else {
+        args.prefixes.clone()
+    };
+
+    for line in render(args, &prefixes) {
+        ctx.writeln(line)?;
+    }
+
+    Ok(())
+}
+
+fn render(args: &FirewallArgs, prefixes: &[IpNet]) -> Vec<String> {
+    let mut v4 = Vec::new();
+    let mut v6 = Vec::new();
+
+    for prefix in prefixes {
+        match prefix {
+            IpNet::V4(ipv4) => v4.push(ipv4.trunc()),
+            IpNet::V6(ipv6) => v6.push(ipv6.trunc()),
+        }
+    }
+
+    match args.format {
+        FirewallFormat::Nftables => nftables(args, &v4, &v6),
+        FirewallFormat::Iptables => iptables(args, &v4, &v6),
+        FirewallFormat::Ipset => ipset(args, &v4, &v6),
+        FirewallFormat::Cisco => cisco(args, &v4, &v6),
+    }
+}
+
+fn join<T: ToString>(prefixes: &[T]) -> String {
+    prefixes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn nftables(args: &FirewallArgs, v4: &[Ipv4Net], v6: &[Ipv6Net]) -> Vec<String> {
+    let table = &args.table;
+    let chain = args.chain.as_deref().unwrap_or("input");
+    let verdict = match args.action {
+        Action::Accept => "accept",
+        Action::Drop => "drop",
+    };
+    let mut lines = Vec::new();
+
+    let families = [
+        ("v4", "ipv4_addr", "ip", join(v4), v4.is_empty()),
+        ("v6", "ipv6_addr", "ip6", join(v6), v6.is_empty()),
+    ];
+
+    for (suffix, kind, protocol, elements, empty) in families {
+        if empty {
+            continue;
+        }
+
+        let set = format!("{}_{suffix}", args.name);
+        lines.push(format!(
+            "add set {table} {set} {{ type {kind}; flags interval; auto-merge; }}"
+        ));
+        lines.push(format!("add element {table} {set} {{ {elements} }}"));
+        lines.push(format!(
+            "add rule {table} {chain} {protocol} saddr @{set} {verdict}"
+        ));
+    }
+
+    lines
+}
+
+fn iptables(args: &FirewallArgs, v4: &[Ipv4Net], v6: &[Ipv6Net]) -> Vec<String> {
+    let chain = args.chain.as_deref().unwrap_or("INPUT");
+    let target = match args.action {
+        Action::Accept => "ACCEPT",
+        Action::Drop => "DROP",
+    };
+
+    v4.iter()
+        .map(|prefix| format!("iptables -A {chain} -s {prefix} -j {target}"))
+        .chain(
+            v6.iter()
+                .map(|prefix| format!("ip6tables -A {chain} -s {prefix} -j {target}")),
+        )
+        .collect()
+}
+
+/// Input for `ipset restore`.
+fn ipset(args: &FirewallArgs, v4: &[Ipv4Net], v6: &[Ipv6Net]) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if !v4.is_empty() {
+        let set = format!("{}_v4", args.name);
+        lines.push(format!("create {set} hash:net family inet -exist"));
+        lines.extend(v4.iter().map(|prefix| format!("add {set} {prefix} -exist")));
+    }
+
+    if !v6.is_empty() {
+        let set = format!("{}_v6", args.name);
+        lines.push(format!("create {set} hash:net family inet6 -exist"));
+        lines.extend(v6.iter().map(|prefix| format!("add {set} {prefix} -exist")));
+    }
+
+    lines
+}
+
+fn cisco(args: &FirewallArgs, v4: &[Ipv4Net], v6: &[Ipv6Net]) -> Vec<String> {
+    let action = match args.action {
+        Action::Accept => "permit",
+        Action::Drop => "deny",
+    };
+    let mut lines = Vec::new();
+
+    if !v4.is_empty() {
+        lines.push(format!("ip access-list standard {}_v4", args.name));
+
+        for prefix in v4 {
+            // Same value as the summary's "Cisco wildcard" line
+            let wildcard = !prefix.netmask();
+
+            if prefix.prefix_len() == 32 {
+                lines.push(format!(" {action} host {}", prefix.addr()));
+            } else {
+                lines.push(format!(" {action} {} {wildcard}", prefix.addr()));
+            }
+        }
+    }
+
+    if !v6.is_empty() {
+        lines.push(format!("ipv6 access-list {}_v6", args.name));
+        lines.extend(
+            v6.iter()
+                .map(|prefix| format!(" {action} ipv6 {prefix} any")),
+        );
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn args(format: FirewallFormat, prefixes: &[&str]) -> FirewallArgs {
+        FirewallArgs {
+            prefixes: prefixes
+                .iter()
+                .map(|prefix| IpNet::from_str(prefix).unwrap())
+                .collect(),
+            format,
+            action: Action::Drop,
+            name: "blocked".to_string(),
+            chain: None,
+            table: "inet filter".to_string(),
+            aggregate: false,
+        }
+    }
+
+    #[test]
+    fn renders_nftables_sets_per_family() {
+        let args = args(
+            FirewallFormat::Nftables,
+            &["10.0.0.0/8", "192.168.0.0/16", "2001:db8::/32"],
+        );
+
+        assert_eq!(
+            render(&args, &args.prefixes),
+            [
+                "add set inet filter blocked_v4 { type ipv4_addr; flags interval; auto-merge; }",
+                "add element inet filter blocked_v4 { 10.0.0.0/8, 192.168.0.0/16 }",
+                "add rule inet filter input ip saddr @blocked_v4 drop",
+                "add set inet filter blocked_v6 { type ipv6_addr; flags interval; auto-merge; }",
+                "add element inet filter blocked_v6 { 2001:db8::/32 }",
+                "add rule inet filter input ip6 saddr @blocked_v6 drop",
+            ]
+        );
+    }
+
+    #[test]
+    fn lets_nftables_merge_overlapping_prefixes() {
+        let args = args(FirewallFormat::Nftables, &["10.0.0.0/8", "10.1.0.0/16"]);
+
+        assert_eq!(
+            render(&args, &args.prefixes)[..2],
+            [
+                "add set inet filter blocked_v4 { type ipv4_addr; flags interval; auto-merge; }",
+                "add element inet filter blocked_v4 { 10.0.0.0/8, 10.1.0.0/16 }",
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_cisco_acl_with_wildcard_masks() {
+        let args = args(
+            FirewallFormat::Cisco,
+            &["10.0.0.0/8", "1.2.3.4/32", "2001:db8::/32"],
+        );
+
+        assert_eq!(
+            render(&args, &args.prefixes),
+            [
+                "ip access-list standard blocked_v4",
+                " deny 10.0.0.0 0.255.255.255",
+                " deny host 1.2.3.4",
+                "ipv6 access-list blocked_v6",
+                " deny ipv6 2001:db8::/32 any",
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_iptables_and_ipset() {
+        let iptables = args(FirewallFormat::Iptables, &["10.0.0.0/8", "2001:db8::/32"]);
+
+        assert_eq!(
+            render(&iptables, &iptables.prefixes),
+            [
+                "iptables -A INPUT -s 10.0.0.0/8 -j DROP",
+                "ip6tables -A INPUT -s 2001:db8::/32 -j DROP",
+            ]
+        );
+
+        let ipset = args(FirewallFormat::Ipset, &["10.0.0.0/8"]);
+
+        assert_eq!(
+            render(&ipset, &ipset.prefixes),
+            [
+                "create blocked_v4 hash:net family inet -exist",
+                "add blocked_v4 10.0.0.0/8 -exist",
+            ]
+        );
+    }
+}
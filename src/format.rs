@@ -0,0 +1,39 @@
+use clap::ValueEnum;
+
+/// How listings such as split results are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Csv,
+    Tsv,
+}
+
+impl OutputFormat {
+    /// Field separator for delimited formats, or `None` for human-readable text.
+    pub fn delimiter(self) -> Option<&'static str> {
+        match self {
+            OutputFormat::Text => None,
+            OutputFormat::Csv => Some(","),
+            OutputFormat::Tsv => Some("\t"),
+        }
+    }
+
+    /// Column names for delimited split listings, or `None` for text.
+    pub fn header(self) -> Option<String> {
+        let delimiter = self.delimiter()?;
+
+        Some(
+            [
+                "index",
+                "cidr",
+                "network",
+                "last_address",
+                "first_usable",
+                "last_usable",
+                "size",
+            ]
+            .join(delimiter),
+        )
+    }
+}
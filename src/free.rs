@@ -0,0 +1,173 @@
+use clap::Args;
+use ipnet::IpNet;
+use std::io::Write;
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::NetworkDisplay;
+use crate::parse::parse_network;
+
+#[derive(Args)]
+pub struct FreeArgs {
+    /// Parent network to search
+    #[arg(value_parser = parse_network)]
+    network: IpNet,
+    /// Prefixes already in use
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_network)]
+    used: Vec<IpNet>,
+    /// Find free subnets of this prefix length instead of listing free blocks
+    #[arg(short, long)]
+    size: Option<u8>,
+    /// List every free subnet of --size rather than the first
+    #[arg(short, long, requires = "size")]
+    all: bool,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &FreeArgs) -> Result<()> {
+    match args.network {
+        IpNet::V4(ipv4) => {
+            let used: Vec<_> = args
+                .used
+                .iter()
+                .filter_map(|net| match net {
+                    IpNet::V4(net) => Some(*net),
+                    _ => None,
+                })
+                .collect();
+
+            list_free(ctx, &ipv4.trunc(), &used, args)
+        }
+        IpNet::V6(ipv6) => {
+            let used: Vec<_> = args
+                .used
+                .iter()
+                .filter_map(|net| match net {
+                    IpNet::V6(net) => Some(*net),
+                    _ => None,
+                })
+                .collect();
+
+            list_free(ctx, &ipv6.trunc(), &used, args)
+        }
+    }
+}
+
+fn list_free<W: Write, E: Write, N: NetworkDisplay + Copy>(
+    ctx: &mut Ctx<W, E>,
+    network: &N,
+    used: &[N],
+    args: &FreeArgs,
+) -> Result<()> {
+    let Some(size) = args.size else {
+        ctx.writeln(format!("-[{} : {network}] - 0\n", N::IP_VERSION))?;
+        ctx.writeln("[Free blocks]".to_string())?;
+
+        for block in network.free_blocks(used)? {
+            ctx.writeln(block)?;
+        }
+
+        return Ok(());
+    };
+
+    let mut subnets = network.free_subnets(size, used)?.peekable();
+
+    if subnets.peek().is_none() {
+        return Err(Error::SpaceExhausted(size, network.to_string()));
+    }
+
+    ctx.writeln(format!("-[{} : {network}] - 0\n", N::IP_VERSION))?;
+    ctx.writeln(format!("[Free /{size} subnets]"))?;
+
+    for subnet in subnets.take(if args.all { usize::MAX } else { 1 }) {
+        ctx.writeln(subnet)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+
+    use pretty_assertions::assert_eq;
+
+    fn args(used: &[&str], size: Option<u8>, all: bool) -> FreeArgs {
+        FreeArgs {
+            network: IpNet::from_str("10.0.0.0/22").unwrap(),
+            used: used
+                .iter()
+                .map(|net| IpNet::from_str(net).unwrap())
+                .collect(),
+            size,
+            all,
+        }
+    }
+
+    #[test]
+    fn lists_minimal_free_blocks() {
+        let mut ctx = create_test_ctx();
+
+        run(
+            &mut ctx,
+            &args(&["10.0.1.0/25", "10.0.3.0/24"], None, false),
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "-[ipv4 : 10.0.0.0/22] - 0
+
+[Free blocks]
+10.0.0.0/24
+10.0.1.128/25
+10.0.2.0/24
+"
+        );
+    }
+
+    #[test]
+    fn finds_first_and_all_free_subnets() {
+        let mut ctx = create_test_ctx();
+
+        run(&mut ctx, &args(&["10.0.0.0/24"], Some(24), false)).unwrap();
+        run(
+            &mut ctx,
+            &args(&["10.0.0.0/24", "10.0.2.0/25"], Some(24), true),
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "-[ipv4 : 10.0.0.0/22] - 0
+
+[Free /24 subnets]
+10.0.1.0/24
+-[ipv4 : 10.0.0.0/22] - 0
+
+[Free /24 subnets]
+10.0.1.0/24
+10.0.3.0/24
+"
+        );
+    }
+
+    #[test]
+    fn reports_when_nothing_fits() {
+        let mut ctx = create_test_ctx();
+
+        let e = run(
+            &mut ctx,
+            &args(
+                &["10.0.0.0/23", "10.0.2.0/24", "10.0.3.0/25"],
+                Some(24),
+                false,
+            ),
+        )
+        .unwrap_err();
+
+        assert!(matches!(e, Error::SpaceExhausted(24, _)));
+    }
+}
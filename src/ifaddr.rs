@@ -0,0 +1,74 @@
+use ipnet::IpNet;
+
+use crate::error::Result;
+
+/// Returns every IPv4/IPv6 network configured on the named local interface.
+pub fn interface_networks(name: &str) -> Result<Vec<IpNet>> {
+    Ok(local_networks()?
+        .into_iter()
+        .filter(|(interface, _)| interface == name)
+        .map(|(_, net)| net)
+        .collect())
+}
+
+/// Returns `(interface name, network)` pairs for every address on the host.
+#[cfg(unix)]
+pub fn local_networks() -> Result<Vec<(String, IpNet)>> {
+    use nix::ifaddrs::getifaddrs;
+    use std::net::IpAddr;
+
+    let mut networks = Vec::new();
+
+    for ifaddr in getifaddrs().map_err(std::io::Error::from)? {
+        let (Some(address), Some(netmask)) = (ifaddr.address, ifaddr.netmask) else {
+            continue;
+        };
+
+        let (ip, mask) =
+            if let (Some(ip), Some(mask)) = (address.as_sockaddr_in(), netmask.as_sockaddr_in()) {
+                (IpAddr::V4(ip.ip()), IpAddr::V4(mask.ip()))
+            } else if let (Some(ip), Some(mask)) =
+                (address.as_sockaddr_in6(), netmask.as_sockaddr_in6())
+            {
+                (IpAddr::V6(ip.ip()), IpAddr::V6(mask.ip()))
+            } else {
+                continue;
+            };
+
+        // Skip interfaces reporting a netmask that isn't a contiguous prefix
+        let Ok(network) = IpNet::with_netmask(ip, mask) else {
+            continue;
+        };
+
+        networks.push((ifaddr.interface_name, network));
+    }
+
+    Ok(networks)
+}
+
+#[cfg(not(unix))]
+pub fn local_networks() -> Result<Vec<(String, IpNet)>> {
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn finds_loopback_networks() {
+        let networks = interface_networks("lo").unwrap();
+
+        assert!(networks.contains(&IpNet::from_str("127.0.0.1/8").unwrap()));
+    }
+
+    #[test]
+    fn returns_nothing_for_unknown_interface() {
+        let networks = interface_networks("iprs-does-not-exist0").unwrap();
+
+        assert!(networks.is_empty());
+    }
+}
@@ -1,39 +1,134 @@
+pub mod hosts;
 pub mod ipv4;
 pub mod ipv6;
 pub mod traits;
 
 use std::io::Write;
+use std::net::IpAddr;
 
 use crate::context::Ctx;
 use crate::error::Result;
+use crate::format::OutputFormat;
+use crate::interface::hosts::HostQuery;
+use crate::interface::traits::{Navigation, SummaryOptions};
+use crate::rng::NetworkRng;
+use crate::template::Template;
 
 use ipnet::IpNet;
 
 pub trait Interface {
-    fn summarize<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>) -> Result<()>;
-    fn split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, split: u8) -> Result<()>;
-    fn random_split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, split: u8) -> Result<()>;
+    fn summarize<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        options: SummaryOptions,
+    ) -> Result<()>;
+    fn split<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        split: u8,
+        format: OutputFormat,
+    ) -> Result<()>;
+    fn random_split<W: Write, E: Write, R: NetworkRng>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        split: u8,
+        count: usize,
+        avoid: &[IpNet],
+        rng: &mut R,
+        options: SummaryOptions,
+    ) -> Result<()>;
+    fn list_hosts<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, query: HostQuery) -> Result<()>;
+    fn random_host<W: Write, E: Write, R: NetworkRng>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        exclude: &[IpAddr],
+        rng: &mut R,
+    ) -> Result<()>;
+    fn render_template<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        template: &Template,
+        split: Option<u8>,
+    ) -> Result<()>;
+    fn navigate(&self, navigation: Navigation) -> Result<Self>
+    where
+        Self: Sized;
 }
 
 impl Interface for IpNet {
-    fn summarize<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>) -> Result<()> {
+    fn summarize<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        options: SummaryOptions,
+    ) -> Result<()> {
         match self {
-            IpNet::V4(ipv4) => ipv4.summarize(ctx),
-            IpNet::V6(ipv6) => ipv6.summarize(ctx),
+            IpNet::V4(ipv4) => Interface::summarize(ipv4, ctx, options),
+            IpNet::V6(ipv6) => Interface::summarize(ipv6, ctx, options),
         }
     }
 
-    fn split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, mask: u8) -> Result<()> {
+    fn split<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        mask: u8,
+        format: OutputFormat,
+    ) -> Result<()> {
         match self {
-            IpNet::V4(ipv4) => ipv4.split(ctx, mask),
-            IpNet::V6(ipv6) => ipv6.split(ctx, mask),
+            IpNet::V4(ipv4) => ipv4.split(ctx, mask, format),
+            IpNet::V6(ipv6) => ipv6.split(ctx, mask, format),
         }
     }
 
-    fn random_split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, split: u8) -> Result<()> {
+    fn random_split<W: Write, E: Write, R: NetworkRng>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        split: u8,
+        count: usize,
+        avoid: &[IpNet],
+        rng: &mut R,
+        options: SummaryOptions,
+    ) -> Result<()> {
         match self {
-            IpNet::V4(ipv4) => ipv4.random_split(ctx, split),
-            IpNet::V6(ipv6) => ipv6.random_split(ctx, split),
+            IpNet::V4(ipv4) => ipv4.random_split(ctx, split, count, avoid, rng, options),
+            IpNet::V6(ipv6) => ipv6.random_split(ctx, split, count, avoid, rng, options),
+        }
+    }
+
+    fn list_hosts<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, query: HostQuery) -> Result<()> {
+        match self {
+            IpNet::V4(ipv4) => ipv4.list_hosts(ctx, query),
+            IpNet::V6(ipv6) => ipv6.list_hosts(ctx, query),
+        }
+    }
+
+    fn random_host<W: Write, E: Write, R: NetworkRng>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        exclude: &[IpAddr],
+        rng: &mut R,
+    ) -> Result<()> {
+        match self {
+            IpNet::V4(ipv4) => ipv4.random_host(ctx, exclude, rng),
+            IpNet::V6(ipv6) => ipv6.random_host(ctx, exclude, rng),
+        }
+    }
+
+    fn render_template<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        template: &Template,
+        split: Option<u8>,
+    ) -> Result<()> {
+        match self {
+            IpNet::V4(ipv4) => Interface::render_template(ipv4, ctx, template, split),
+            IpNet::V6(ipv6) => Interface::render_template(ipv6, ctx, template, split),
+        }
+    }
+
+    fn navigate(&self, navigation: Navigation) -> Result<Self> {
+        match self {
+            IpNet::V4(ipv4) => Ok(IpNet::V4(ipv4.navigate(navigation)?)),
+            IpNet::V6(ipv6) => Ok(IpNet::V6(ipv6.navigate(navigation)?)),
         }
     }
 }
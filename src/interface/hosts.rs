@@ -0,0 +1,111 @@
+use num_traits::cast;
+use std::marker::PhantomData;
+
+use crate::interface::traits::NetworkPrimitive;
+
+/// Which usable host addresses of a network to list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostQuery {
+    Page {
+        offset: u64,
+        limit: Option<u64>,
+    },
+    /// Zero-based index; negative indices count back from the last host.
+    Nth(i128),
+}
+
+/// Inclusive range of host addresses, iterable from either end.
+#[derive(Clone, Debug)]
+pub struct Hosts<A, P> {
+    front: P,
+    back: P,
+    exhausted: bool,
+    address: PhantomData<A>,
+}
+
+impl<A: From<P>, P: NetworkPrimitive> Hosts<A, P> {
+    pub fn new(first: P, last: P) -> Self {
+        Hosts {
+            front: first,
+            back: last,
+            exhausted: first > last,
+            address: PhantomData,
+        }
+    }
+
+    /// First and last remaining hosts, or `None` once exhausted.
+    pub fn bounds(&self) -> Option<(P, P)> {
+        (!self.exhausted).then_some((self.front, self.back))
+    }
+
+    /// Skips the first `count` hosts without walking them one by one.
+    pub fn skip_hosts(mut self, count: u64) -> Self {
+        match cast::<u64, P>(count).and_then(|count| self.front.checked_add(&count)) {
+            Some(front) if !self.exhausted && front <= self.back => self.front = front,
+            _ => self.exhausted = true,
+        }
+
+        self
+    }
+
+    /// Looks up a host by zero-based index, counting from the end when negative.
+    pub fn nth_host(&self, index: i128) -> Option<A> {
+        if self.exhausted {
+            return None;
+        }
+
+        let offset = cast::<u128, P>(if index < 0 {
+            index.unsigned_abs() - 1
+        } else {
+            index.unsigned_abs()
+        })?;
+
+        if offset > self.back - self.front {
+            return None;
+        }
+
+        if index < 0 {
+            Some(A::from(self.back - offset))
+        } else {
+            Some(A::from(self.front + offset))
+        }
+    }
+}
+
+impl<A: From<P>, P: NetworkPrimitive> Iterator for Hosts<A, P> {
+    type Item = A;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted {
+            return None;
+        }
+
+        let host = self.front;
+
+        if self.front == self.back {
+            self.exhausted = true;
+        } else {
+            self.front = self.front + P::one();
+        }
+
+        Some(A::from(host))
+    }
+}
+
+impl<A: From<P>, P: NetworkPrimitive> DoubleEndedIterator for Hosts<A, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.exhausted {
+            return None;
+        }
+
+        let host = self.back;
+
+        if self.front == self.back {
+            self.exhausted = true;
+        } else {
+            self.back = self.back - P::one();
+        }
+
+        Some(A::from(host))
+    }
+}
@@ -1,23 +1,27 @@
-use std::{io::Write, net::Ipv4Addr};
+use std::{
+    io::Write,
+    net::{IpAddr, Ipv4Addr},
+};
 
 use crate::context::Ctx;
 use crate::error::Result;
-use crate::interface::{traits::*, Interface};
-use crate::rng::DefaultRng;
-use ipnet::Ipv4Net;
+use crate::format::OutputFormat;
+use crate::interface::{
+    hosts::{HostQuery, Hosts},
+    traits::*,
+    Interface,
+};
+use crate::rng::NetworkRng;
+use crate::template::{SummaryField, Template};
+use ipnet::{IpNet, Ipv4Net};
 
 trait Ranges {
-    fn addresses_in_network(&self) -> u32;
     fn network_range(&self) -> String;
     fn usable_range(&self) -> Option<String>;
+    fn reverse_name(&self) -> String;
 }
 
 impl Ranges for Ipv4Net {
-    fn addresses_in_network(&self) -> u32 {
-        let prefix_len = self.prefix_len();
-        2u32.saturating_pow(32 - prefix_len as u32)
-    }
-
     fn network_range(&self) -> String {
         format!("{} - {}", self.network(), self.broadcast())
     }
@@ -27,14 +31,17 @@ impl Ranges for Ipv4Net {
             return None;
         }
 
-        let first = u32::from(self.network()).saturating_add(1);
-        let last = u32::from(self.broadcast()).saturating_sub(1);
+        let mut hosts = self.usable_hosts();
+        let first = hosts.next()?;
+        let last = hosts.next_back().unwrap_or(first);
 
-        Some(format!(
-            "{} - {}",
-            Ipv4Addr::from(first),
-            Ipv4Addr::from(last)
-        ))
+        Some(format!("{first} - {last}"))
+    }
+
+    fn reverse_name(&self) -> String {
+        let [a, b, c, d] = self.addr().octets();
+
+        format!("{d}.{c}.{b}.{a}.in-addr.arpa")
     }
 }
 
@@ -57,56 +64,141 @@ impl NetworkCore for Ipv4Net {
     fn subnets(&self, prefix_len: u8) -> Result<impl Iterator<Item = Self>> {
         Ok(self.subnets(prefix_len)?)
     }
+
+    fn usable_hosts(&self) -> Hosts<Self::Address, Self::Primitive> {
+        let network = u32::from(self.network());
+        let broadcast = u32::from(self.broadcast());
+
+        // /31 and /32 have no network or broadcast address to exclude (RFC 3021)
+        if self.prefix_len() > 30 {
+            return Hosts::new(network, broadcast);
+        }
+
+        Hosts::new(network.saturating_add(1), broadcast.saturating_sub(1))
+    }
 }
 
 impl NetworkDisplay for Ipv4Net {
     const IP_VERSION: &'static str = "ipv4";
     const FORMAT_WIDTH: usize = 15;
+    const BINARY_GROUPS: &'static [(u8, char)] = &[(8, '.')];
+
+    fn summary_fields(&self) -> Vec<SummaryField> {
+        let (first_usable, last_usable) = match self.usable_hosts().bounds() {
+            Some((first, last)) => (Ipv4Addr::from(first), Ipv4Addr::from(last)),
+            None => (self.network(), self.broadcast()),
+        };
+        let usable_range = match self.usable_range() {
+            Some(usable_range) => SummaryField::new("usable_range", "Usable range", usable_range),
+            None => SummaryField::hidden("usable_range", ""),
+        };
+
+        vec![
+            SummaryField::hidden("version", Self::IP_VERSION),
+            SummaryField::hidden("cidr", self),
+            SummaryField::not_applicable("expanded"),
+            SummaryField::new("host", "Host address", self.addr()),
+            SummaryField::new(
+                "host_decimal",
+                "Host address (decimal)",
+                u32::from(self.addr()),
+            ),
+            SummaryField::new(
+                "host_hex",
+                "Host address (hex)",
+                format!("{:X}", u32::from(self.addr())),
+            ),
+            SummaryField::new(
+                "host_octal",
+                "Host address (octal)",
+                match u32::from(self.addr()) {
+                    0 => "0".to_string(),
+                    host => format!("0{host:o}"),
+                },
+            ),
+            SummaryField::new(
+                "host_dotted_hex",
+                "Host address (dot hex)",
+                self.addr()
+                    .octets()
+                    .map(|octet| format!("0x{octet:02X}"))
+                    .join("."),
+            ),
+            // Templates only, as --binary already prints the address bits
+            SummaryField::hidden(
+                "host_binary",
+                self.addr()
+                    .octets()
+                    .map(|octet| format!("{octet:08b}"))
+                    .join("."),
+            ),
+            SummaryField::new(
+                "host_mapped",
+                "IPv4-mapped IPv6",
+                self.addr().to_ipv6_mapped(),
+            ),
+            SummaryField::not_applicable("base85"),
+            SummaryField::not_applicable("prefix_masked"),
+            SummaryField::not_applicable("id_masked"),
+            SummaryField::not_applicable("type"),
+            SummaryField::new("network", "Network address", self.network()),
+            SummaryField::new("netmask", "Network mask", self.netmask()),
+            SummaryField::new("prefix", "Network mask (bits)", self.prefix_len()),
+            SummaryField::new(
+                "netmask_hex",
+                "Network mask (hex)",
+                format!("{:X}", u32::from(self.netmask())),
+            ),
+            SummaryField::new("broadcast", "Broadcast address", self.broadcast()),
+            SummaryField::new("wildcard", "Cisco wildcard", !self.netmask()),
+            SummaryField::new("size", "Addresses in network", self.address_count()),
+            SummaryField::new("network_range", "Network range", self.network_range()),
+            usable_range,
+            SummaryField::hidden("first_usable", first_usable),
+            SummaryField::hidden("last_usable", last_usable),
+            SummaryField::hidden("reverse", self.reverse_name()),
+        ]
+    }
 
     fn from_addr_prefix(addr: Self::Address, prefix: u8) -> Result<Self> {
         Ok(Ipv4Net::new(addr, prefix)?)
     }
 }
 
+impl NetworkNavigation for Ipv4Net {}
+
 impl<W: Write, E: Write> NetworkSummarize<W, E> for Ipv4Net {
-    fn summarize(&self, ctx: &mut Ctx<W, E>) -> Result<()> {
+    fn summarize(&self, ctx: &mut Ctx<W, E>, options: SummaryOptions) -> Result<()> {
         ctx.writeln(format!("-[ipv4 : {self}] - 0\n\n[CIDR]"))?;
 
-        ctx.writeln(Self::format_attribute("Host address", self.addr()))?;
-        ctx.writeln(Self::format_attribute(
-            "Host address (decimal)",
-            u32::from(self.addr()),
-        ))?;
-        ctx.writeln(Self::format_attribute(
-            "Host address (hex)",
-            format!("{:X}", u32::from(self.addr())),
-        ))?;
-        ctx.writeln(Self::format_attribute("Network address", self.network()))?;
-        ctx.writeln(Self::format_attribute("Network mask", self.netmask()))?;
-        ctx.writeln(Self::format_attribute(
-            "Network mask (bits)",
-            self.prefix_len(),
-        ))?;
-        ctx.writeln(Self::format_attribute(
-            "Network mask (hex)",
-            format!("{:X}", u32::from(self.netmask())),
-        ))?;
-        ctx.writeln(Self::format_attribute(
-            "Broadcast address",
-            self.broadcast(),
-        ))?;
-        ctx.writeln(Self::format_attribute("Cisco wildcard", !self.netmask()))?;
-        ctx.writeln(Self::format_attribute(
-            "Addresses in network",
-            self.addresses_in_network(),
-        ))?;
-        ctx.writeln(Self::format_attribute(
-            "Network range",
-            self.network_range(),
-        ))?;
-
-        if let Some(usable_range) = self.usable_range() {
-            ctx.writeln(Self::format_attribute("Usable range", usable_range))?;
+        for field in self.summary_fields() {
+            if let Some(label) = field.label {
+                ctx.writeln(Self::format_attribute(label, field.value))?;
+            }
+        }
+
+        if options.binary {
+            ctx.writeln("\n[CIDR bitmaps]".to_string())?;
+            ctx.writeln(Self::format_attribute(
+                "Host address",
+                self.to_binary(u32::from(self.addr())),
+            ))?;
+            ctx.writeln(Self::format_attribute(
+                "Network address",
+                self.to_binary(u32::from(self.network())),
+            ))?;
+            ctx.writeln(Self::format_attribute(
+                "Network mask",
+                self.to_binary(u32::from(self.netmask())),
+            ))?;
+            ctx.writeln(Self::format_attribute(
+                "Broadcast address",
+                self.to_binary(u32::from(self.broadcast())),
+            ))?;
+            ctx.writeln(Self::format_attribute(
+                "Cisco wildcard",
+                self.to_binary(!u32::from(self.netmask())),
+            ))?;
         }
 
         Ok(())
@@ -114,17 +206,76 @@ impl<W: Write, E: Write> NetworkSummarize<W, E> for Ipv4Net {
 }
 
 impl Interface for Ipv4Net {
-    fn summarize<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>) -> Result<()> {
-        NetworkSummarize::summarize(self, ctx)
+    fn summarize<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        options: SummaryOptions,
+    ) -> Result<()> {
+        NetworkSummarize::summarize(self, ctx, options)
+    }
+
+    fn split<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        mask: u8,
+        format: OutputFormat,
+    ) -> Result<()> {
+        NetworkDisplay::split(self, ctx, mask, format)
     }
 
-    fn split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, mask: u8) -> Result<()> {
-        NetworkDisplay::split(self, ctx, mask)
+    fn random_split<W: Write, E: Write, R: NetworkRng>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        split: u8,
+        count: usize,
+        avoid: &[IpNet],
+        rng: &mut R,
+        options: SummaryOptions,
+    ) -> Result<()> {
+        let avoid: Vec<_> = avoid
+            .iter()
+            .filter_map(|net| match net {
+                IpNet::V4(net) => Some(*net),
+                _ => None,
+            })
+            .collect();
+        NetworkDisplay::summarize_random_split(self, ctx, split, count, &avoid, rng, options)
     }
 
-    fn random_split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, split: u8) -> Result<()> {
-        let mut rng = DefaultRng;
-        NetworkDisplay::summarize_random_split(self, ctx, split, &mut rng)
+    fn list_hosts<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, query: HostQuery) -> Result<()> {
+        NetworkDisplay::list_hosts(self, ctx, query)
+    }
+
+    fn random_host<W: Write, E: Write, R: NetworkRng>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        exclude: &[IpAddr],
+        rng: &mut R,
+    ) -> Result<()> {
+        let exclude: Vec<_> = exclude
+            .iter()
+            .filter_map(|addr| match addr {
+                IpAddr::V4(addr) => Some(*addr),
+                _ => None,
+            })
+            .collect();
+        NetworkDisplay::summarize_random_host(self, ctx, &exclude, rng)
+    }
+
+    fn render_template<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        template: &Template,
+        split: Option<u8>,
+    ) -> Result<()> {
+        match split {
+            Some(mask) => NetworkDisplay::split_template(self, ctx, mask, template),
+            None => NetworkDisplay::render_template(self, ctx, template),
+        }
+    }
+
+    fn navigate(&self, navigation: Navigation) -> Result<Self> {
+        NetworkNavigation::navigate(self, navigation)
     }
 }
 
@@ -134,8 +285,8 @@ mod tests {
 
     use super::*;
     use crate::context::test_util::{create_test_ctx, get_output_as_string};
+    use crate::error::Error;
     use crate::rng::RandomRangeGenerator;
-    use crate::Error;
 
     use pretty_assertions::assert_eq;
 
@@ -169,6 +320,9 @@ mod tests {
 Host address            - 10.1.1.1
 Host address (decimal)  - 167837953
 Host address (hex)      - A010101
+Host address (octal)    - 01200200401
+Host address (dot hex)  - 0x0A.0x01.0x01.0x01
+IPv4-mapped IPv6        - ::ffff:10.1.1.1
 Network address         - 10.1.1.1
 Network mask            - 255.255.255.255
 Network mask (bits)     - 32
@@ -181,12 +335,32 @@ Network range           - 10.1.1.1 - 10.1.1.1
         let ip = Ipv4Net::from_str("10.1.1.1/32").unwrap();
         let mut ctx = create_test_ctx();
 
-        Interface::summarize(&ip, &mut ctx).unwrap();
+        Interface::summarize(&ip, &mut ctx, SummaryOptions::default()).unwrap();
         let output = get_output_as_string(&ctx);
 
         assert_eq!(output, expected)
     }
 
+    #[test]
+    fn summarizes_in_binary() {
+        let ip = Ipv4Net::from_str("192.168.1.77/20").unwrap();
+        let mut ctx = create_test_ctx();
+
+        Interface::summarize(&ip, &mut ctx, SummaryOptions { binary: true }).unwrap();
+        let output = get_output_as_string(&ctx);
+
+        assert!(output.ends_with(
+            "
+[CIDR bitmaps]
+Host address            - 11000000.10101000.0000|0001.01001101
+Network address         - 11000000.10101000.0000|0000.00000000
+Network mask            - 11111111.11111111.1111|0000.00000000
+Broadcast address       - 11000000.10101000.0000|1111.11111111
+Cisco wildcard          - 00000000.00000000.0000|1111.11111111
+"
+        ));
+    }
+
     #[test]
     fn includes_usable_range_below_31() {
         let expected = "-[ipv4 : 10.1.1.1/30] - 0
@@ -195,6 +369,9 @@ Network range           - 10.1.1.1 - 10.1.1.1
 Host address            - 10.1.1.1
 Host address (decimal)  - 167837953
 Host address (hex)      - A010101
+Host address (octal)    - 01200200401
+Host address (dot hex)  - 0x0A.0x01.0x01.0x01
+IPv4-mapped IPv6        - ::ffff:10.1.1.1
 Network address         - 10.1.1.0
 Network mask            - 255.255.255.252
 Network mask (bits)     - 30
@@ -209,7 +386,7 @@ Usable range            - 10.1.1.1 - 10.1.1.2
         let ip = Ipv4Net::from_str("10.1.1.1/30").unwrap();
         let mut ctx = create_test_ctx();
 
-        Interface::summarize(&ip, &mut ctx).unwrap();
+        Interface::summarize(&ip, &mut ctx, SummaryOptions::default()).unwrap();
         let output = get_output_as_string(&ctx);
 
         assert_eq!(output, expected)
@@ -232,7 +409,21 @@ Network - 1.2.3.112       - 1.2.3.127
         let ip = Ipv4Net::from_str("1.2.3.4/25").unwrap();
         let mut ctx = create_test_ctx();
 
-        Interface::split(&ip, &mut ctx, 28).unwrap();
+        Interface::split(&ip, &mut ctx, 28, OutputFormat::Text).unwrap();
+        let output = get_output_as_string(&ctx);
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn splits_a_range_as_csv_rows() {
+        let expected = "0,1.2.3.0/26,1.2.3.0,1.2.3.63,1.2.3.1,1.2.3.62,64
+1,1.2.3.64/26,1.2.3.64,1.2.3.127,1.2.3.65,1.2.3.126,64
+";
+        let ip = Ipv4Net::from_str("1.2.3.4/25").unwrap();
+        let mut ctx = create_test_ctx();
+
+        Interface::split(&ip, &mut ctx, 26, OutputFormat::Csv).unwrap();
         let output = get_output_as_string(&ctx);
 
         assert_eq!(output, expected);
@@ -243,7 +434,7 @@ Network - 1.2.3.112       - 1.2.3.127
         let ip = Ipv4Net::from_str("1.2.3.4/29").unwrap();
         let mut ctx = create_test_ctx();
 
-        let e = Interface::split(&ip, &mut ctx, 24).unwrap_err();
+        let e = Interface::split(&ip, &mut ctx, 24, OutputFormat::Text).unwrap_err();
 
         assert!(matches!(e, Error::SplitSmallerThanPrefixLen(24, 29)));
     }
@@ -274,4 +465,184 @@ Network - 1.2.3.112       - 1.2.3.127
 
         assert_eq!(output.to_string(), "230.141.13.0/24");
     }
+
+    #[test]
+    fn navigates_to_adjacent_blocks() {
+        let ip = Ipv4Net::from_str("10.0.1.7/24").unwrap();
+
+        assert_eq!(ip.next(1).unwrap().to_string(), "10.0.2.0/24");
+        assert_eq!(ip.next(3).unwrap().to_string(), "10.0.4.0/24");
+        assert_eq!(ip.prev(1).unwrap().to_string(), "10.0.0.0/24");
+    }
+
+    #[test]
+    fn navigates_to_enclosing_networks() {
+        let ip = Ipv4Net::from_str("10.0.1.0/24").unwrap();
+
+        assert_eq!(ip.parent().unwrap().to_string(), "10.0.0.0/23");
+        assert_eq!(
+            NetworkNavigation::supernet(&ip, 8).unwrap().to_string(),
+            "10.0.0.0/8"
+        );
+        assert_eq!(ip.sibling().unwrap().to_string(), "10.0.0.0/24");
+    }
+
+    #[test]
+    fn reports_navigation_off_the_address_space() {
+        let last = Ipv4Net::from_str("255.255.255.0/24").unwrap();
+        let first = Ipv4Net::from_str("0.0.0.0/24").unwrap();
+        let all = Ipv4Net::from_str("0.0.0.0/0").unwrap();
+
+        assert!(matches!(last.next(1), Err(Error::NextOutOfRange(1, _))));
+        assert!(matches!(first.prev(2), Err(Error::PrevOutOfRange(2, _))));
+        assert!(matches!(all.parent(), Err(Error::NoParent(_))));
+        assert!(matches!(all.sibling(), Err(Error::NoParent(_))));
+        assert!(matches!(
+            NetworkNavigation::supernet(&first, 25),
+            Err(Error::SupernetLargerThanPrefixLen(25, 24))
+        ));
+    }
+
+    #[test]
+    fn lists_a_page_of_usable_hosts() {
+        let expected = "-[ipv4 : 10.0.0.0/29] - 0
+
+[Host addresses]
+10.0.0.3
+10.0.0.4
+";
+        let ip = Ipv4Net::from_str("10.0.0.0/29").unwrap();
+        let mut ctx = create_test_ctx();
+        let query = HostQuery::Page {
+            offset: 2,
+            limit: Some(2),
+        };
+
+        Interface::list_hosts(&ip, &mut ctx, query).unwrap();
+        let output = get_output_as_string(&ctx);
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn excludes_network_and_broadcast_from_hosts() {
+        let ip = Ipv4Net::from_str("10.0.0.0/30").unwrap();
+        let hosts: Vec<_> = ip.usable_hosts().collect();
+
+        assert_eq!(
+            hosts,
+            [Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)]
+        );
+
+        let point_to_point = Ipv4Net::from_str("10.0.0.0/31").unwrap();
+
+        assert_eq!(point_to_point.usable_hosts().count(), 2);
+    }
+
+    #[test]
+    fn fetches_the_nth_host_from_either_end() {
+        let ip = Ipv4Net::from_str("10.0.0.0/24").unwrap();
+        let hosts = ip.usable_hosts();
+
+        assert_eq!(hosts.nth_host(0), Some(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(hosts.nth_host(4), Some(Ipv4Addr::new(10, 0, 0, 5)));
+        assert_eq!(hosts.nth_host(-1), Some(Ipv4Addr::new(10, 0, 0, 254)));
+        assert_eq!(hosts.nth_host(254), None);
+        assert_eq!(hosts.nth_host(-255), None);
+    }
+
+    #[test]
+    fn reports_out_of_range_host_index() {
+        let ip = Ipv4Net::from_str("10.0.0.0/29").unwrap();
+        let mut ctx = create_test_ctx();
+
+        let e = Interface::list_hosts(&ip, &mut ctx, HostQuery::Nth(6)).unwrap_err();
+
+        assert!(matches!(e, Error::HostIndexOutOfRange(6, _)));
+        assert_eq!(get_output_as_string(&ctx), "");
+    }
+
+    #[test]
+    fn random_host_skips_excluded_addresses() {
+        let ip = Ipv4Net::from_str("10.0.0.0/29").unwrap();
+        let exclude = [Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 3)];
+
+        let mut first = MockRng::new(0);
+        let host = ip.generate_random_host(&exclude, &mut first).unwrap();
+
+        assert_eq!(host, Ipv4Addr::new(10, 0, 0, 2));
+
+        let mut second = MockRng::new(1);
+        let host = ip.generate_random_host(&exclude, &mut second).unwrap();
+
+        assert_eq!(host, Ipv4Addr::new(10, 0, 0, 4));
+    }
+
+    #[test]
+    fn random_host_reports_exhausted_network() {
+        let ip = Ipv4Net::from_str("10.0.0.0/30").unwrap();
+        let exclude = [Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)];
+        let mut mock_rng = MockRng::new(0);
+
+        let e = ip
+            .generate_random_host(&exclude, &mut mock_rng)
+            .unwrap_err();
+
+        assert!(matches!(e, Error::NoFreeHosts(_)));
+    }
+
+    #[test]
+    fn lists_free_blocks_around_used_subnets() {
+        let ip = Ipv4Net::from_str("10.0.0.0/24").unwrap();
+        let used = [Ipv4Net::from_str("10.0.0.64/26").unwrap()];
+
+        let free: Vec<_> = ip
+            .free_blocks(&used)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(free, ["10.0.0.0/26", "10.0.0.128/25"]);
+    }
+
+    #[test]
+    fn random_splits_avoid_existing_allocations() {
+        let ip = Ipv4Net::from_str("10.0.0.0/24").unwrap();
+        let avoid = [Ipv4Net::from_str("10.0.0.0/25").unwrap()];
+        let mut mock_rng = MockRng::new(0);
+
+        let picks: Vec<_> = ip
+            .generate_random_splits(26, 2, &avoid, &mut mock_rng)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(picks, ["10.0.0.128/26", "10.0.0.192/26"]);
+    }
+
+    #[test]
+    fn random_splits_report_exhausted_space() {
+        let ip = Ipv4Net::from_str("10.0.0.0/24").unwrap();
+        let mut mock_rng = MockRng::new(0);
+
+        let e = ip
+            .generate_random_splits(25, 3, &[], &mut mock_rng)
+            .unwrap_err();
+
+        assert!(matches!(e, Error::SpaceExhausted(25, _)));
+    }
+
+    #[test]
+    fn renders_summary_through_a_template() {
+        let ip = Ipv4Net::from_str("10.1.1.1/24").unwrap();
+        let template = Template::parse("{network}/{prefix} {wildcard} {reverse}").unwrap();
+        let mut ctx = create_test_ctx();
+
+        Interface::render_template(&ip, &mut ctx, &template, None).unwrap();
+        let output = get_output_as_string(&ctx);
+
+        assert_eq!(output, "10.1.1.0/24 0.0.0.255 1.1.1.10.in-addr.arpa\n");
+    }
 }
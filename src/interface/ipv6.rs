@@ -1,11 +1,16 @@
-use ipnet::Ipv6Net;
-use std::{io::Write, net::Ipv6Addr};
+use ipnet::{IpNet, Ipv6Net};
+use std::{
+    io::Write,
+    net::{IpAddr, Ipv6Addr},
+};
 
 use crate::{
     context::Ctx,
     error::Result,
-    interface::{traits::*, Interface},
-    rng::DefaultRng,
+    format::OutputFormat,
+    interface::{hosts::HostQuery, traits::*, Interface},
+    rng::NetworkRng,
+    template::{SummaryField, Template},
 };
 
 trait PrintableProperties {
@@ -13,8 +18,14 @@ trait PrintableProperties {
     fn subnet_prefix_masked(&self) -> String;
     fn address_id_masked(&self) -> String;
     fn address_type(&self) -> &str;
+    fn reverse_name(&self) -> String;
+    fn base85_address(&self) -> String;
 }
 
+/// Digits of the RFC 1924 base 85 encoding, in order.
+const BASE85_DIGITS: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
 impl PrintableProperties for Ipv6Net {
     fn expanded_address(&self) -> String {
         let addr = self.addr();
@@ -64,6 +75,29 @@ impl PrintableProperties for Ipv6Net {
 
         "Reserved by IETF"
     }
+
+    fn reverse_name(&self) -> String {
+        let nibbles: Vec<String> = format!("{:032x}", u128::from(self.addr()))
+            .chars()
+            .rev()
+            .map(String::from)
+            .collect();
+
+        format!("{}.ip6.arpa", nibbles.join("."))
+    }
+
+    /// RFC 1924: the address as a 20-digit base 85 number.
+    fn base85_address(&self) -> String {
+        let mut value = u128::from(self.addr());
+        let mut digits = [b'0'; 20];
+
+        for digit in digits.iter_mut().rev() {
+            *digit = BASE85_DIGITS[(value % 85) as usize];
+            value /= 85;
+        }
+
+        String::from_utf8_lossy(&digits).into_owned()
+    }
 }
 
 impl NetworkCore for Ipv6Net {
@@ -90,58 +124,177 @@ impl NetworkCore for Ipv6Net {
 impl NetworkDisplay for Ipv6Net {
     const IP_VERSION: &'static str = "ipv6";
     const FORMAT_WIDTH: usize = 39;
+    const BINARY_GROUPS: &'static [(u8, char)] = &[(16, ':'), (4, ' ')];
+
+    fn summary_fields(&self) -> Vec<SummaryField> {
+        let network = self.trunc().network();
+        let broadcast = self.trunc().broadcast();
+        let (first_usable, last_usable) = match self.usable_hosts().bounds() {
+            Some((first, last)) => (Ipv6Addr::from(first), Ipv6Addr::from(last)),
+            None => (network, broadcast),
+        };
+
+        vec![
+            SummaryField::hidden("version", Self::IP_VERSION),
+            SummaryField::hidden("cidr", self),
+            SummaryField::new("expanded", "Expanded Address", self.expanded_address()),
+            SummaryField::new("host", "Compressed Address", self.addr()),
+            SummaryField::new("host_decimal", "Decimal Address", u128::from(self.addr())),
+            SummaryField::hidden("host_hex", format!("{:X}", u128::from(self.addr()))),
+            SummaryField::not_applicable("host_octal"),
+            SummaryField::not_applicable("host_dotted_hex"),
+            SummaryField::hidden(
+                "host_binary",
+                self.addr()
+                    .segments()
+                    .map(|segment| format!("{segment:016b}"))
+                    .join(":"),
+            ),
+            SummaryField::not_applicable("host_mapped"),
+            SummaryField::new("base85", "Base85 Address", self.base85_address()),
+            SummaryField::new(
+                "prefix_masked",
+                "Subnet Prefix (masked)",
+                self.subnet_prefix_masked(),
+            ),
+            SummaryField::new("id_masked", "Address ID (masked)", self.address_id_masked()),
+            SummaryField::new("netmask", "Prefix address", self.trunc().netmask()),
+            SummaryField::new("prefix", "Prefix length", self.prefix_len()),
+            SummaryField::hidden(
+                "netmask_hex",
+                format!("{:X}", u128::from(self.trunc().netmask())),
+            ),
+            SummaryField::new("type", "Address type", self.address_type()),
+            SummaryField::hidden("network", network),
+            SummaryField::hidden("broadcast", broadcast),
+            SummaryField::hidden("wildcard", !self.trunc().netmask()),
+            SummaryField::hidden("network_range", format!("{network} - {broadcast}")),
+            SummaryField::hidden("usable_range", format!("{first_usable} - {last_usable}")),
+            SummaryField::hidden("first_usable", first_usable),
+            SummaryField::hidden("last_usable", last_usable),
+            SummaryField::hidden("size", self.address_count()),
+            SummaryField::hidden("reverse", self.reverse_name()),
+        ]
+    }
 
     fn from_addr_prefix(addr: Self::Address, prefix: u8) -> Result<Self> {
         Ok(Ipv6Net::new(addr, prefix)?)
     }
 }
 
+impl NetworkNavigation for Ipv6Net {}
+
 impl<W: Write, E: Write> NetworkSummarize<W, E> for Ipv6Net {
-    fn summarize(&self, ctx: &mut Ctx<W, E>) -> Result<()> {
+    fn summarize(&self, ctx: &mut Ctx<W, E>, options: SummaryOptions) -> Result<()> {
         ctx.writeln(format!("-[ipv6 : {self}] - 0\n"))?;
         ctx.writeln("[IPV6 INFO]".to_string())?;
 
-        ctx.writeln(Self::format_attribute(
-            "Expanded Address",
-            self.expanded_address(),
-        ))?;
-        ctx.writeln(Self::format_attribute("Compressed Address", self.addr()))?;
-        ctx.writeln(Self::format_attribute(
-            "Subnet Prefix (masked)",
-            self.subnet_prefix_masked(),
-        ))?;
-        ctx.writeln(Self::format_attribute(
-            "Address ID (masked)",
-            self.address_id_masked(),
-        ))?;
-        ctx.writeln(Self::format_attribute(
-            "Prefix address",
-            self.trunc().netmask(),
-        ))?;
-        ctx.writeln(Self::format_attribute("Prefix length", self.prefix_len()))?;
-        ctx.writeln(Self::format_attribute("Address type", self.address_type()))?;
+        for field in self.summary_fields() {
+            if let Some(label) = field.label {
+                ctx.writeln(Self::format_attribute(label, field.value))?;
+            }
+        }
 
         let network_range_start = self.trunc().network();
         let network_range_end = self.trunc().broadcast();
         ctx.writeln(format!("{: <24}- {network_range_start} -", "Network range"))?;
         ctx.writeln(format!("{: <25} {}", " ", network_range_end))?;
 
+        if options.binary {
+            ctx.writeln("\n[IPV6 BITMAPS]".to_string())?;
+            ctx.writeln(Self::format_attribute(
+                "Compressed Address",
+                self.to_binary(u128::from(self.addr())),
+            ))?;
+            ctx.writeln(Self::format_attribute(
+                "Network address",
+                self.to_binary(u128::from(self.trunc().network())),
+            ))?;
+            ctx.writeln(Self::format_attribute(
+                "Prefix address",
+                self.to_binary(u128::from(self.netmask())),
+            ))?;
+            ctx.writeln(Self::format_attribute(
+                "Last address",
+                self.to_binary(u128::from(self.trunc().broadcast())),
+            ))?;
+        }
+
         Ok(())
     }
 }
 
 impl Interface for Ipv6Net {
-    fn summarize<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>) -> Result<()> {
-        NetworkSummarize::summarize(self, ctx)
+    fn summarize<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        options: SummaryOptions,
+    ) -> Result<()> {
+        NetworkSummarize::summarize(self, ctx, options)
+    }
+
+    fn split<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        mask: u8,
+        format: OutputFormat,
+    ) -> Result<()> {
+        NetworkDisplay::split(self, ctx, mask, format)
+    }
+
+    fn random_split<W: Write, E: Write, R: NetworkRng>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        split: u8,
+        count: usize,
+        avoid: &[IpNet],
+        rng: &mut R,
+        options: SummaryOptions,
+    ) -> Result<()> {
+        let avoid: Vec<_> = avoid
+            .iter()
+            .filter_map(|net| match net {
+                IpNet::V6(net) => Some(*net),
+                _ => None,
+            })
+            .collect();
+        NetworkDisplay::summarize_random_split(self, ctx, split, count, &avoid, rng, options)
     }
 
-    fn split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, mask: u8) -> Result<()> {
-        NetworkDisplay::split(self, ctx, mask)
+    fn list_hosts<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, query: HostQuery) -> Result<()> {
+        NetworkDisplay::list_hosts(self, ctx, query)
     }
 
-    fn random_split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, split: u8) -> Result<()> {
-        let mut rng = DefaultRng;
-        NetworkDisplay::summarize_random_split(self, ctx, split, &mut rng)
+    fn random_host<W: Write, E: Write, R: NetworkRng>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        exclude: &[IpAddr],
+        rng: &mut R,
+    ) -> Result<()> {
+        let exclude: Vec<_> = exclude
+            .iter()
+            .filter_map(|addr| match addr {
+                IpAddr::V6(addr) => Some(*addr),
+                _ => None,
+            })
+            .collect();
+        NetworkDisplay::summarize_random_host(self, ctx, &exclude, rng)
+    }
+
+    fn render_template<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        template: &Template,
+        split: Option<u8>,
+    ) -> Result<()> {
+        match split {
+            Some(mask) => NetworkDisplay::split_template(self, ctx, mask, template),
+            None => NetworkDisplay::render_template(self, ctx, template),
+        }
+    }
+
+    fn navigate(&self, navigation: Navigation) -> Result<Self> {
+        NetworkNavigation::navigate(self, navigation)
     }
 }
 
@@ -151,8 +304,8 @@ mod tests {
 
     use super::*;
     use crate::context::test_util::{create_test_ctx, get_output_as_string};
+    use crate::error::Error;
     use crate::rng::RandomRangeGenerator;
-    use crate::Error;
     use pretty_assertions::assert_eq;
 
     fn ip_to_u128(ip: &str) -> u128 {
@@ -177,6 +330,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn encodes_base85_per_rfc1924() {
+        let ip = Ipv6Net::from_str("1080::8:800:200c:417a/128").unwrap();
+
+        assert_eq!(ip.base85_address(), "4)+k&C#VzJ4br>0wv%Yp");
+    }
+
+    #[test]
+    fn groups_binary_by_nibble_and_hextet() {
+        let ip = Ipv6Net::from_str("2001:db8::/32").unwrap();
+
+        assert_eq!(
+            ip.to_binary(ip_to_u128("2001:db8::")),
+            "0010 0000 0000 0001:0000 1101 1011 1000|0000 0000 0000 0000:0000 0000 0000 0000:\
+             0000 0000 0000 0000:0000 0000 0000 0000:0000 0000 0000 0000:0000 0000 0000 0000"
+        );
+    }
+
     #[test]
     fn sumarizes_an_interface() {
         let expected = "-[ipv6 : 3bc7:a1c8:8d4:f9fc:3ed1:bfed:f539:a271/64] - 0
@@ -184,6 +355,8 @@ mod tests {
 [IPV6 INFO]
 Expanded Address        - 3bc7:a1c8:08d4:f9fc:3ed1:bfed:f539:a271
 Compressed Address      - 3bc7:a1c8:8d4:f9fc:3ed1:bfed:f539:a271
+Decimal Address         - 79461000142470510845897372722829828721
+Base85 Address          - HaG?sk4uf#g4~w}dE68&
 Subnet Prefix (masked)  - 3bc7:a1c8:8d4:f9fc::/64
 Address ID (masked)     - ::3ed1:bfed:f539:a271/64
 Prefix address          - ffff:ffff:ffff:ffff::
@@ -195,7 +368,7 @@ Network range           - 3bc7:a1c8:8d4:f9fc:: -
         let ip = Ipv6Net::from_str("3bc7:a1c8:8d4:f9fc:3ed1:bfed:f539:a271/64").unwrap();
         let mut ctx = create_test_ctx();
 
-        Interface::summarize(&ip, &mut ctx).unwrap();
+        Interface::summarize(&ip, &mut ctx, SummaryOptions::default()).unwrap();
         let output = get_output_as_string(&ctx);
 
         assert_eq!(output, expected)
@@ -218,7 +391,21 @@ Network - ffff::7000:0:0                          - ffff::7fff:ffff:ffff
         let ip = Ipv6Net::from_str("ffff::/81").unwrap();
         let mut ctx = create_test_ctx();
 
-        Interface::split(&ip, &mut ctx, 84).unwrap();
+        Interface::split(&ip, &mut ctx, 84, OutputFormat::Text).unwrap();
+        let output = get_output_as_string(&ctx);
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn splits_a_range_as_tsv_rows() {
+        let expected = "0\t::/1\t::\t7fff:ffff:ffff:ffff:ffff:ffff:ffff:ffff\t::\t7fff:ffff:ffff:ffff:ffff:ffff:ffff:ffff\t170141183460469231731687303715884105728
+1\t8000::/1\t8000::\tffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff\t8000::\tffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff\t170141183460469231731687303715884105728
+";
+        let ip = Ipv6Net::from_str("::/0").unwrap();
+        let mut ctx = create_test_ctx();
+
+        Interface::split(&ip, &mut ctx, 1, OutputFormat::Tsv).unwrap();
         let output = get_output_as_string(&ctx);
 
         assert_eq!(output, expected);
@@ -229,7 +416,7 @@ Network - ffff::7000:0:0                          - ffff::7fff:ffff:ffff
         let ip = Ipv6Net::from_str("1234:5678::/64").unwrap();
         let mut ctx = create_test_ctx();
 
-        let e = Interface::split(&ip, &mut ctx, 25).unwrap_err();
+        let e = Interface::split(&ip, &mut ctx, 25, OutputFormat::Text).unwrap_err();
 
         assert!(matches!(e, Error::SplitSmallerThanPrefixLen(25, 64)));
     }
@@ -260,4 +447,85 @@ Network - ffff::7000:0:0                          - ffff::7fff:ffff:ffff
 
         assert_eq!(output.to_string(), "4cc7:8e7:b232:e2dd::/64");
     }
+
+    #[test]
+    fn navigates_between_networks() {
+        let ip = Ipv6Net::from_str("2001:db8:0:1::/64").unwrap();
+
+        assert_eq!(ip.next(1).unwrap().to_string(), "2001:db8:0:2::/64");
+        assert_eq!(ip.prev(1).unwrap().to_string(), "2001:db8::/64");
+        assert_eq!(ip.parent().unwrap().to_string(), "2001:db8::/63");
+        assert_eq!(
+            NetworkNavigation::supernet(&ip, 32).unwrap().to_string(),
+            "2001:db8::/32"
+        );
+        assert_eq!(ip.sibling().unwrap().to_string(), "2001:db8::/64");
+    }
+
+    #[test]
+    fn reports_navigation_off_the_address_space() {
+        let last = Ipv6Net::from_str("ffff:ffff:ffff:ffff::/64").unwrap();
+
+        assert!(matches!(last.next(1), Err(Error::NextOutOfRange(1, _))));
+        assert!(matches!(
+            Ipv6Net::from_str("::/0").unwrap().parent(),
+            Err(Error::NoParent(_))
+        ));
+    }
+
+    #[test]
+    fn pages_through_hosts_of_the_whole_address_space() {
+        let ip = Ipv6Net::from_str("::/0").unwrap();
+        let hosts = ip.usable_hosts();
+
+        assert_eq!(hosts.nth_host(-1), Some(Ipv6Addr::from(u128::MAX)));
+
+        let page: Vec<_> = hosts.skip_hosts(u64::MAX).take(2).collect();
+
+        assert_eq!(
+            page,
+            [
+                Ipv6Addr::from(u64::MAX as u128),
+                Ipv6Addr::from(u64::MAX as u128 + 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn random_host_picks_an_interface_id() {
+        let ip = Ipv6Net::from_str("2001:db8::/64").unwrap();
+
+        let mut mock_rng = MockRng::new(ip_to_u128("::3ed1:bfed:f539:a271"));
+        let host = ip.generate_random_host(&[], &mut mock_rng).unwrap();
+
+        assert_eq!(host.to_string(), "2001:db8::3ed1:bfed:f539:a271");
+    }
+
+    #[test]
+    fn free_split_covers_the_whole_address_space() {
+        let ip = Ipv6Net::from_str("::/0").unwrap();
+        let mut mock_rng = MockRng::new(u128::from(u64::MAX));
+
+        let split = ip.generate_free_split(128, &[], &mut mock_rng).unwrap();
+
+        assert_eq!(
+            split.to_string(),
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128"
+        );
+    }
+
+    #[test]
+    fn renders_split_through_a_template() {
+        let ip = Ipv6Net::from_str("2001:db8::/63").unwrap();
+        let template = Template::parse("{cidr} {broadcast}").unwrap();
+        let mut ctx = create_test_ctx();
+
+        Interface::render_template(&ip, &mut ctx, &template, Some(64)).unwrap();
+        let output = get_output_as_string(&ctx);
+
+        assert_eq!(
+            output,
+            "2001:db8::/64 2001:db8::ffff:ffff:ffff:ffff\n2001:db8:0:1::/64 2001:db8:0:1:ffff:ffff:ffff:ffff\n"
+        );
+    }
 }
@@ -1,26 +1,33 @@
-use num_traits::{One, PrimInt, Zero};
+use num_traits::{cast, CheckedAdd, CheckedMul, CheckedSub, One, PrimInt, Zero};
 use std::fmt;
 use std::io::Write;
 
 use crate::{
     error::{Error, Result},
-    rng::RandomRangeGenerator,
+    format::OutputFormat,
+    interface::hosts::{HostQuery, Hosts},
+    rng::{random_up_to, RandomRangeGenerator},
+    template::{SummaryField, Template},
     Ctx,
 };
 
-pub trait NetworkPrimitive: PrimInt {
+pub trait NetworkPrimitive: PrimInt + fmt::Display {
     const BITS: u8;
     const MAX: Self;
+    /// Size of the whole address space, which is one more than `MAX` can hold.
+    const ADDRESS_SPACE: &'static str;
 }
 
 impl NetworkPrimitive for u32 {
     const BITS: u8 = 32;
     const MAX: Self = u32::MAX;
+    const ADDRESS_SPACE: &'static str = "4294967296";
 }
 
 impl NetworkPrimitive for u128 {
     const BITS: u8 = 128;
     const MAX: Self = u128::MAX;
+    const ADDRESS_SPACE: &'static str = "340282366920938463463374607431768211456";
 }
 
 pub trait NetworkCore {
@@ -41,54 +48,386 @@ pub trait NetworkCore {
     fn primitive_to_addr(&self, prim: Self::Primitive) -> Self::Address {
         prim.into()
     }
+
+    /// The network address with host bits cleared.
+    fn network_primitive(&self) -> Self::Primitive {
+        self.addr_to_primitive(self.addr()) & prefix_mask(self.prefix_len())
+    }
+
+    /// Addresses that can be assigned to hosts, which is every address unless overridden.
+    fn usable_hosts(&self) -> Hosts<Self::Address, Self::Primitive> {
+        Hosts::new(
+            self.network_primitive(),
+            self.addr_to_primitive(self.broadcast()),
+        )
+    }
+
+    /// Number of addresses in the network, as a string since /0 overflows the primitive.
+    fn address_count(&self) -> String {
+        match block_size::<Self::Primitive>(self.prefix_len()) {
+            Some(size) => size.to_string(),
+            None => Self::Primitive::ADDRESS_SPACE.to_string(),
+        }
+    }
+
+    /// Whether `other` lies entirely within this network.
+    fn covers(&self, other: &Self) -> bool {
+        self.prefix_len() <= other.prefix_len()
+            && other.network_primitive() & prefix_mask(self.prefix_len())
+                == self.network_primitive()
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        self.covers(other) || other.covers(self)
+    }
+}
+
+/// Mask with the leading `prefix_len` bits set.
+pub fn prefix_mask<P: NetworkPrimitive>(prefix_len: u8) -> P {
+    if prefix_len == 0 {
+        return P::zero();
+    }
+
+    !((P::one() << (P::BITS - prefix_len) as usize) - P::one())
+}
+
+/// Number of addresses in a block of `prefix_len`, or `None` if it spans the whole address space.
+pub fn block_size<P: NetworkPrimitive>(prefix_len: u8) -> Option<P> {
+    if prefix_len == 0 {
+        return None;
+    }
+
+    Some(P::one() << (P::BITS - prefix_len) as usize)
 }
 
 pub trait NetworkDisplay: NetworkCore + fmt::Display {
     const IP_VERSION: &'static str;
     const FORMAT_WIDTH: usize;
+    /// Bit group sizes and the separator placed before each group, largest first.
+    const BINARY_GROUPS: &'static [(u8, char)];
 
     fn format_attribute<T: fmt::Display>(name: &str, value: T) -> String {
         format!("{name: <24}- {value}")
     }
 
-    fn split<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, mask: u8) -> Result<()>
+    /// Values shown in the summary, also available to output templates.
+    fn summary_fields(&self) -> Vec<SummaryField>;
+
+    fn render_template<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        template: &Template,
+    ) -> Result<()> {
+        ctx.writeln(template.render(&self.summary_fields())?)?;
+
+        Ok(())
+    }
+
+    /// Renders each subnet of length `mask` through the template.
+    fn split_template<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        mask: u8,
+        template: &Template,
+    ) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let subnets = self
+            .subnets(mask)
+            .map_err(|_| Error::SplitSmallerThanPrefixLen(mask, self.prefix_len()))?;
+
+        for subnet in subnets {
+            subnet.render_template(ctx, template)?;
+        }
+
+        Ok(())
+    }
+
+    /// Lists the subnets of length `mask`. Delimited formats write only the
+    /// rows, as the header is written once for all inputs.
+    fn split<W: Write, E: Write>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        mask: u8,
+        format: OutputFormat,
+    ) -> Result<()>
     where
         Self: Sized,
         Self::Address: fmt::Display,
     {
+        let subnets = self
+            .subnets(mask)
+            .map_err(|_| Error::SplitSmallerThanPrefixLen(mask, self.prefix_len()))?;
+
+        let Some(delimiter) = format.delimiter() else {
+            ctx.writeln(format!("-[{} : {}] - 0\n", Self::IP_VERSION, self))?;
+            ctx.writeln("[Split network]".to_string())?;
+
+            for subnet in subnets {
+                ctx.writeln(format!(
+                    "Network - {:<width$} - {}",
+                    subnet.addr(),
+                    subnet.broadcast(),
+                    width = Self::FORMAT_WIDTH
+                ))?;
+            }
+
+            return Ok(());
+        };
+
+        for (index, subnet) in subnets.enumerate() {
+            let (first_usable, last_usable) = match subnet.usable_hosts().bounds() {
+                Some((first, last)) => (
+                    subnet.primitive_to_addr(first).to_string(),
+                    subnet.primitive_to_addr(last).to_string(),
+                ),
+                None => (String::new(), String::new()),
+            };
+
+            ctx.writeln(
+                [
+                    index.to_string(),
+                    subnet.to_string(),
+                    subnet.addr().to_string(),
+                    subnet.broadcast().to_string(),
+                    first_usable,
+                    last_usable,
+                    subnet.address_count(),
+                ]
+                .join(delimiter),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn list_hosts<W: Write, E: Write>(&self, ctx: &mut Ctx<W, E>, query: HostQuery) -> Result<()> {
+        let hosts = self.usable_hosts();
+        let nth = match query {
+            HostQuery::Nth(index) => Some(
+                hosts
+                    .nth_host(index)
+                    .ok_or_else(|| Error::HostIndexOutOfRange(index, self.to_string()))?,
+            ),
+            HostQuery::Page { .. } => None,
+        };
+
         ctx.writeln(format!("-[{} : {}] - 0\n", Self::IP_VERSION, self))?;
-        ctx.writeln("[Split network]".to_string())?;
-
-        match self.subnets(mask) {
-            Ok(subnets) => {
-                for subnet in subnets {
-                    ctx.writeln(format!(
-                        "Network - {:<width$} - {}",
-                        subnet.addr(),
-                        subnet.broadcast(),
-                        width = Self::FORMAT_WIDTH
-                    ))?;
-                }
-                Ok(())
+        ctx.writeln("[Host addresses]".to_string())?;
+
+        if let Some(host) = nth {
+            ctx.writeln(host)?;
+        } else if let HostQuery::Page { offset, limit } = query {
+            let limit = limit.map_or(usize::MAX, |limit| {
+                usize::try_from(limit).unwrap_or(usize::MAX)
+            });
+
+            for host in hosts.skip_hosts(offset).take(limit) {
+                ctx.writeln(host)?;
             }
-            Err(_) => Err(Error::SplitSmallerThanPrefixLen(mask, self.prefix_len())),
         }
+
+        Ok(())
     }
 
     fn summarize_random_split<W: Write, E: Write, R: RandomRangeGenerator<Self::Primitive>>(
         &self,
         ctx: &mut Ctx<W, E>,
         split: u8,
+        count: usize,
+        avoid: &[Self],
         rng: &mut R,
+        options: SummaryOptions,
     ) -> Result<()>
     where
-        Self: Sized + NetworkSummarize<W, E>,
+        Self: Sized + Copy + NetworkSummarize<W, E>,
     {
-        let address = self.generate_random_split(split, rng)?;
-        address.summarize(ctx)?;
+        let addresses = self.generate_random_splits(split, count, avoid, rng)?;
+
+        for (i, address) in addresses.iter().enumerate() {
+            if i > 0 {
+                ctx.writeln("\n-")?;
+            }
+
+            address.summarize(ctx, options)?;
+        }
+
         Ok(())
     }
 
+    /// Picks `count` distinct random subnets that don't overlap each other or `avoid`.
+    fn generate_random_splits<R: RandomRangeGenerator<Self::Primitive>>(
+        &self,
+        split: u8,
+        count: usize,
+        avoid: &[Self],
+        rng: &mut R,
+    ) -> Result<Vec<Self>>
+    where
+        Self: Sized + Copy,
+    {
+        let mut used = avoid.to_vec();
+        let mut picks = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let candidate = self.generate_random_split(split, rng)?;
+            let pick = if used.iter().any(|used| used.overlaps(&candidate)) {
+                self.generate_free_split(split, &used, rng)?
+            } else {
+                candidate
+            };
+
+            used.push(pick);
+            picks.push(pick);
+        }
+
+        Ok(picks)
+    }
+
+    /// Picks uniformly among the subnets of length `split` that don't overlap `used`.
+    fn generate_free_split<R: RandomRangeGenerator<Self::Primitive>>(
+        &self,
+        split: u8,
+        used: &[Self],
+        rng: &mut R,
+    ) -> Result<Self>
+    where
+        Self: Sized + Copy,
+    {
+        // Each block with the index of its last subnet, as a /0 block split
+        // into full-width subnets holds one more than the primitive can count
+        let candidates: Vec<(Self, Self::Primitive)> = self
+            .free_blocks(used)?
+            .into_iter()
+            .filter(|block| block.prefix_len() <= split)
+            .map(|block| {
+                let bits = split - block.prefix_len();
+                let last = if bits >= Self::Primitive::BITS {
+                    Self::Primitive::MAX
+                } else {
+                    (Self::Primitive::one() << bits as usize) - Self::Primitive::one()
+                };
+
+                (block, last)
+            })
+            .collect();
+        // The free subnets never outnumber the subnets of this network, so the
+        // index of the last one always fits
+        let last_index = candidates.iter().fold(None, |total, (_, last)| {
+            Some(total.map_or(*last, |total| total + *last + Self::Primitive::one()))
+        });
+
+        let Some(last_index) = last_index else {
+            return Err(Error::SpaceExhausted(split, self.to_string()));
+        };
+
+        let mut index = random_up_to(rng, last_index);
+
+        for (block, last) in candidates {
+            if index <= last {
+                let size = block_size::<Self::Primitive>(split).unwrap_or(Self::Primitive::zero());
+                let network = block.network_primitive() + index * size;
+
+                return Self::from_addr_prefix(self.primitive_to_addr(network), split);
+            }
+
+            index = index - last - Self::Primitive::one();
+        }
+
+        Err(Error::SpaceExhausted(split, self.to_string()))
+    }
+
+    /// Minimal set of blocks in this network left over after removing `used`.
+    fn free_blocks(&self, used: &[Self]) -> Result<Vec<Self>>
+    where
+        Self: Sized + Copy,
+    {
+        let mut free = vec![Self::from_addr_prefix(
+            self.primitive_to_addr(self.network_primitive()),
+            self.prefix_len(),
+        )?];
+
+        for used in used {
+            let mut remaining = Vec::with_capacity(free.len());
+
+            for block in free {
+                if !block.overlaps(used) {
+                    remaining.push(block);
+                    continue;
+                }
+
+                // Halve the block down to `used`, keeping each half that doesn't hold it
+                let mut current = block;
+                while current.prefix_len() < used.prefix_len() {
+                    let (lower, upper) = current.halves()?;
+
+                    if lower.covers(used) {
+                        remaining.push(upper);
+                        current = lower;
+                    } else {
+                        remaining.push(lower);
+                        current = upper;
+                    }
+                }
+            }
+
+            free = remaining;
+        }
+
+        free.sort_by_key(|block| (block.network_primitive(), block.prefix_len()));
+
+        Ok(free)
+    }
+
+    /// Subnets of length `prefix_len` that don't overlap `used`, in address order.
+    fn free_subnets(&self, prefix_len: u8, used: &[Self]) -> Result<impl Iterator<Item = Self>>
+    where
+        Self: Sized + Copy,
+    {
+        if prefix_len < self.prefix_len() {
+            return Err(Error::SplitSmallerThanPrefixLen(
+                prefix_len,
+                self.prefix_len(),
+            ));
+        } else if prefix_len > Self::Primitive::BITS {
+            return Err(Error::SplitTooBig(Self::Primitive::BITS, prefix_len));
+        }
+
+        Ok(self
+            .free_blocks(used)?
+            .into_iter()
+            .filter(move |block| block.prefix_len() <= prefix_len)
+            .flat_map(move |block| {
+                let last = block.addr_to_primitive(block.broadcast());
+                let size = block_size::<Self::Primitive>(prefix_len);
+
+                std::iter::successors(Some(block.network_primitive()), move |start| {
+                    size.and_then(|size| start.checked_add(&size))
+                        .filter(|next| *next <= last)
+                })
+                .filter_map(move |start| {
+                    Self::from_addr_prefix(block.primitive_to_addr(start), prefix_len).ok()
+                })
+            }))
+    }
+
+    /// The two subnets one bit longer than this network.
+    fn halves(&self) -> Result<(Self, Self)>
+    where
+        Self: Sized,
+    {
+        let prefix_len = self.prefix_len() + 1;
+        let size = block_size::<Self::Primitive>(prefix_len)
+            .ok_or(Error::SplitTooBig(Self::Primitive::BITS, prefix_len))?;
+        let lower = self.network_primitive();
+
+        Ok((
+            Self::from_addr_prefix(self.primitive_to_addr(lower), prefix_len)?,
+            Self::from_addr_prefix(self.primitive_to_addr(lower | size), prefix_len)?,
+        ))
+    }
+
     fn generate_random_split<R: RandomRangeGenerator<Self::Primitive>>(
         &self,
         split: u8,
@@ -122,12 +461,207 @@ pub trait NetworkDisplay: NetworkCore + fmt::Display {
         Self::from_addr_prefix(self.primitive_to_addr(new_prefix), split)
     }
 
+    fn summarize_random_host<W: Write, E: Write, R: RandomRangeGenerator<Self::Primitive>>(
+        &self,
+        ctx: &mut Ctx<W, E>,
+        exclude: &[Self::Address],
+        rng: &mut R,
+    ) -> Result<()> {
+        let host = self.generate_random_host(exclude, rng)?;
+
+        ctx.writeln(format!("-[{} : {}] - 0\n", Self::IP_VERSION, self))?;
+        ctx.writeln("[Random host]".to_string())?;
+        ctx.writeln(host)?;
+
+        Ok(())
+    }
+
+    /// Picks a random usable host, skipping any address in `exclude`.
+    fn generate_random_host<R: RandomRangeGenerator<Self::Primitive>>(
+        &self,
+        exclude: &[Self::Address],
+        rng: &mut R,
+    ) -> Result<Self::Address> {
+        let no_free_hosts = || Error::NoFreeHosts(self.to_string());
+        let (first, last) = self.usable_hosts().bounds().ok_or_else(no_free_hosts)?;
+
+        let mut excluded: Vec<Self::Primitive> = exclude
+            .iter()
+            .map(|addr| self.addr_to_primitive(*addr))
+            .filter(|addr| (first..=last).contains(addr))
+            .collect();
+        excluded.sort();
+        excluded.dedup();
+
+        // Both bounds are inclusive, so `last - first` is one less than the host count
+        let span = last - first;
+        let excluded_count =
+            cast::<usize, Self::Primitive>(excluded.len()).ok_or_else(no_free_hosts)?;
+        if excluded_count > span {
+            return Err(no_free_hosts());
+        }
+
+        let mut host = first + random_up_to(rng, span - excluded_count);
+
+        // Shift past every excluded address at or below the pick to land on the nth free host
+        for excluded in excluded {
+            if excluded > host {
+                break;
+            }
+
+            host = host + Self::Primitive::one();
+        }
+
+        Ok(self.primitive_to_addr(host))
+    }
+
+    /// Formats `value` in binary, grouped per `BINARY_GROUPS`, with a `|` at the
+    /// prefix boundary in place of (or in addition to) the separator there.
+    fn to_binary(&self, value: Self::Primitive) -> String {
+        let prefix_len = self.prefix_len();
+        let mut binary = String::new();
+
+        for bit in 0..Self::Primitive::BITS {
+            let separator = Self::BINARY_GROUPS
+                .iter()
+                .find(|(size, _)| bit > 0 && bit % size == 0)
+                .map(|(_, separator)| *separator);
+
+            if bit == prefix_len && bit > 0 {
+                binary.push('|');
+            } else if let Some(separator) = separator {
+                binary.push(separator);
+            }
+
+            let shift = (Self::Primitive::BITS - 1 - bit) as usize;
+            binary.push(
+                if (value >> shift) & Self::Primitive::one() == Self::Primitive::one() {
+                    '1'
+                } else {
+                    '0'
+                },
+            );
+        }
+
+        binary
+    }
+
     /// Creates a new address from an Address and prefix length.
     fn from_addr_prefix(addr: Self::Address, prefix: u8) -> Result<Self>
     where
         Self: Sized;
 }
 
+/// Optional sections of a network summary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SummaryOptions {
+    /// Show addresses and masks in binary
+    pub binary: bool,
+}
+
 pub trait NetworkSummarize<W: Write, E: Write>: NetworkCore {
-    fn summarize(&self, ctx: &mut Ctx<W, E>) -> Result<()>;
+    fn summarize(&self, ctx: &mut Ctx<W, E>, options: SummaryOptions) -> Result<()>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Navigation {
+    Next(u64),
+    Prev(u64),
+    Parent,
+    Supernet(u8),
+    Sibling,
+}
+
+pub trait NetworkNavigation: NetworkDisplay {
+    fn navigate(&self, navigation: Navigation) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        match navigation {
+            Navigation::Next(steps) => self.next(steps),
+            Navigation::Prev(steps) => self.prev(steps),
+            Navigation::Parent => self.parent(),
+            Navigation::Supernet(prefix_len) => self.supernet(prefix_len),
+            Navigation::Sibling => self.sibling(),
+        }
+    }
+
+    /// The block of the same size `steps` blocks after this one.
+    fn next(&self, steps: u64) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let out_of_range = || Error::NextOutOfRange(steps, self.to_string());
+        let offset = block_size::<Self::Primitive>(self.prefix_len())
+            .zip(cast::<u64, Self::Primitive>(steps))
+            .and_then(|(size, steps)| size.checked_mul(&steps));
+        let network = match offset {
+            Some(offset) => self.network_primitive().checked_add(&offset),
+            None if steps == 0 => Some(self.network_primitive()),
+            None => None,
+        }
+        .ok_or_else(out_of_range)?;
+
+        Self::from_addr_prefix(self.primitive_to_addr(network), self.prefix_len())
+    }
+
+    /// The block of the same size `steps` blocks before this one.
+    fn prev(&self, steps: u64) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let out_of_range = || Error::PrevOutOfRange(steps, self.to_string());
+        let offset = block_size::<Self::Primitive>(self.prefix_len())
+            .zip(cast::<u64, Self::Primitive>(steps))
+            .and_then(|(size, steps)| size.checked_mul(&steps));
+        let network = match offset {
+            Some(offset) => self.network_primitive().checked_sub(&offset),
+            None if steps == 0 => Some(self.network_primitive()),
+            None => None,
+        }
+        .ok_or_else(out_of_range)?;
+
+        Self::from_addr_prefix(self.primitive_to_addr(network), self.prefix_len())
+    }
+
+    /// The enclosing network one bit shorter.
+    fn parent(&self) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        if self.prefix_len() == 0 {
+            return Err(Error::NoParent(self.to_string()));
+        }
+
+        self.supernet(self.prefix_len() - 1)
+    }
+
+    /// The enclosing network with the given prefix length.
+    fn supernet(&self, prefix_len: u8) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        if prefix_len > self.prefix_len() {
+            return Err(Error::SupernetLargerThanPrefixLen(
+                prefix_len,
+                self.prefix_len(),
+            ));
+        }
+
+        let network = self.network_primitive() & prefix_mask(prefix_len);
+
+        Self::from_addr_prefix(self.primitive_to_addr(network), prefix_len)
+    }
+
+    /// The other half of the parent network.
+    fn sibling(&self) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let size = block_size::<Self::Primitive>(self.prefix_len())
+            .ok_or_else(|| Error::NoParent(self.to_string()))?;
+        let network = self.network_primitive() ^ size;
+
+        Self::from_addr_prefix(self.primitive_to_addr(network), self.prefix_len())
+    }
 }
@@ -0,0 +1,480 @@
+use clap::{Args, Subcommand};
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::NetworkDisplay;
+use crate::parse::parse_network;
+
+#[derive(Args)]
+pub struct IpamArgs {
+    /// JSON file holding the pools and allocations
+    #[arg(long, default_value = "ipam.json")]
+    db: PathBuf,
+    #[command(subcommand)]
+    command: IpamCommand,
+}
+
+#[derive(Subcommand)]
+enum IpamCommand {
+    /// Manage the networks allocations are made from
+    #[command(subcommand)]
+    Pool(PoolCommand),
+    /// Allocate the first free subnet of a size from a pool and print it
+    Alloc(AllocArgs),
+    /// Release an allocation by name or network
+    Release { allocation: String },
+    /// List pools and their allocations
+    List,
+    /// Show the details of a pool or allocation
+    Show { name: String },
+}
+
+#[derive(Subcommand)]
+enum PoolCommand {
+    /// Add a pool
+    Add {
+        name: String,
+        #[arg(value_parser = parse_network)]
+        network: IpNet,
+    },
+}
+
+#[derive(Args)]
+struct AllocArgs {
+    /// Prefix length of the allocation, e.g. 26 or /26
+    #[arg(short, long, value_parser = parse_prefix_len)]
+    size: u8,
+    /// Name of the allocation, unique across pools and allocations
+    #[arg(short, long)]
+    name: String,
+    /// Pool to allocate from, required when there is more than one
+    #[arg(short, long)]
+    pool: Option<String>,
+    /// Owner of the allocation
+    #[arg(long)]
+    owner: Option<String>,
+    /// Free-form tags
+    #[arg(long = "tag", value_delimiter = ',')]
+    tags: Vec<String>,
+}
+
+fn parse_prefix_len(size: &str) -> std::result::Result<u8, String> {
+    size.trim_start_matches('/')
+        .parse()
+        .map_err(|_| format!("`{size}` is not a prefix length"))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Store {
+    pools: Vec<Pool>,
+    allocations: Vec<Allocation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Pool {
+    name: String,
+    network: IpNet,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Allocation {
+    name: String,
+    network: IpNet,
+    pool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &IpamArgs) -> Result<()> {
+    match &args.command {
+        IpamCommand::Pool(PoolCommand::Add { name, network }) => {
+            let network = network.trunc();
+
+            update(&args.db, |store| store.add_pool(name, network))?;
+            ctx.writeln(format!("Added pool {name} - {network}"))?;
+        }
+        IpamCommand::Alloc(alloc) => {
+            let network = update(&args.db, |store| store.allocate(alloc))?;
+
+            ctx.writeln(network)?;
+        }
+        IpamCommand::Release { allocation } => {
+            let released = update(&args.db, |store| store.release(allocation))?;
+
+            ctx.writeln(format!("Released {} - {}", released.name, released.network))?;
+        }
+        IpamCommand::List => {
+            let store = read(&args.db)?;
+            let width = store
+                .allocations
+                .iter()
+                .map(|allocation| allocation.name.len())
+                .max()
+                .unwrap_or(0);
+
+            for pool in &store.pools {
+                ctx.writeln(format!("{} - {}", pool.name, pool.network))?;
+
+                for allocation in store.in_pool(&pool.name) {
+                    ctx.writeln(format!(
+                        "  {:<width$} - {}",
+                        allocation.name, allocation.network
+                    ))?;
+                }
+            }
+        }
+        IpamCommand::Show { name } => {
+            let store = read(&args.db)?;
+
+            for (label, value) in store.show(name)? {
+                ctx.writeln(format!("{label: <24}- {value}"))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Store {
+    /// Pools and allocations share one namespace, so `show` can't mix them up.
+    fn check_name_free(&self, name: &str) -> Result<()> {
+        if self.pools.iter().any(|pool| pool.name == name)
+            || self.allocations.iter().any(|a| a.name == name)
+        {
+            return Err(Error::NameInUse(name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn add_pool(&mut self, name: &str, network: IpNet) -> Result<()> {
+        self.check_name_free(name)?;
+
+        if let Some(pool) = self
+            .pools
+            .iter()
+            .find(|pool| pool.network.contains(&network) || network.contains(&pool.network))
+        {
+            return Err(Error::PoolOverlap(network.to_string(), pool.name.clone()));
+        }
+
+        self.pools.push(Pool {
+            name: name.to_string(),
+            network,
+        });
+
+        Ok(())
+    }
+
+    fn in_pool<'a>(&'a self, pool: &'a str) -> impl Iterator<Item = &'a Allocation> {
+        self.allocations
+            .iter()
+            .filter(move |allocation| allocation.pool == pool)
+    }
+
+    fn allocate(&mut self, args: &AllocArgs) -> Result<IpNet> {
+        self.check_name_free(&args.name)?;
+
+        let pool = match (&args.pool, self.pools.as_slice()) {
+            (Some(name), pools) => pools
+                .iter()
+                .find(|pool| &pool.name == name)
+                .ok_or_else(|| Error::UnknownName(name.clone()))?,
+            (None, [pool]) => pool,
+            (None, _) => return Err(Error::PoolRequired),
+        };
+        let used: Vec<IpNet> = self
+            .in_pool(&pool.name)
+            .map(|allocation| allocation.network)
+            .collect();
+
+        let network = match pool.network {
+            IpNet::V4(ipv4) => IpNet::V4(first_free(&ipv4, args.size, &used, |net| match net {
+                IpNet::V4(net) => Some(*net),
+                _ => None,
+            })?),
+            IpNet::V6(ipv6) => IpNet::V6(first_free(&ipv6, args.size, &used, |net| match net {
+                IpNet::V6(net) => Some(*net),
+                _ => None,
+            })?),
+        };
+
+        self.allocations.push(Allocation {
+            name: args.name.clone(),
+            network,
+            pool: pool.name.clone(),
+            owner: args.owner.clone(),
+            tags: args.tags.clone(),
+        });
+
+        Ok(network)
+    }
+
+    fn release(&mut self, allocation: &str) -> Result<Allocation> {
+        let index = self
+            .allocations
+            .iter()
+            .position(|a| a.name == allocation || a.network.to_string() == allocation)
+            .ok_or_else(|| Error::UnknownName(allocation.to_string()))?;
+
+        Ok(self.allocations.remove(index))
+    }
+
+    fn show(&self, name: &str) -> Result<Vec<(&'static str, String)>> {
+        if let Some(pool) = self.pools.iter().find(|pool| pool.name == name) {
+            return Ok(vec![
+                ("Pool", pool.name.clone()),
+                ("Network", pool.network.to_string()),
+                ("Allocations", self.in_pool(&pool.name).count().to_string()),
+            ]);
+        }
+
+        let allocation = self
+            .allocations
+            .iter()
+            .find(|a| a.name == name || a.network.to_string() == name)
+            .ok_or_else(|| Error::UnknownName(name.to_string()))?;
+        let mut fields = vec![
+            ("Allocation", allocation.name.clone()),
+            ("Network", allocation.network.to_string()),
+            ("Pool", allocation.pool.clone()),
+        ];
+
+        if let Some(owner) = &allocation.owner {
+            fields.push(("Owner", owner.clone()));
+        }
+        if !allocation.tags.is_empty() {
+            fields.push(("Tags", allocation.tags.join(", ")));
+        }
+
+        Ok(fields)
+    }
+}
+
+fn first_free<N: NetworkDisplay + Copy>(
+    network: &N,
+    size: u8,
+    used: &[IpNet],
+    select: impl Fn(&IpNet) -> Option<N>,
+) -> Result<N> {
+    let used: Vec<N> = used.iter().filter_map(select).collect();
+
+    let first = network.free_subnets(size, &used)?.next();
+
+    first.ok_or_else(|| Error::SpaceExhausted(size, network.to_string()))
+}
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+
+    lock_path.into()
+}
+
+/// Takes an exclusive lock on a file next to the store, so it stays valid
+/// across the rename that replaces the store itself.
+fn lock(path: &Path) -> Result<File> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(path))?;
+
+    file.lock()?;
+
+    Ok(file)
+}
+
+/// Takes a shared lock for reading. Without a lock file nothing has written
+/// the store yet, so there is nothing to wait for and no file is created.
+fn lock_shared(path: &Path) -> Result<Option<File>> {
+    let file = match File::open(lock_path(path)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    file.lock_shared()?;
+
+    Ok(Some(file))
+}
+
+fn load(path: &Path) -> Result<Store> {
+    match fs::read(path) {
+        Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Store::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn read(path: &Path) -> Result<Store> {
+    let _lock = lock_shared(path)?;
+
+    load(path)
+}
+
+/// Applies `change` under an exclusive lock, replacing the store atomically
+/// only if it succeeds.
+fn update<T>(path: &Path, change: impl FnOnce(&mut Store) -> Result<T>) -> Result<T> {
+    let _lock = lock(path)?;
+    let mut store = load(path)?;
+    let result = change(&mut store)?;
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(".{}.tmp", std::process::id()));
+
+    let written =
+        write(&tmp_path, &store).and_then(|()| fs::rename(&tmp_path, path).map_err(Error::from));
+
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+
+        return Err(e);
+    }
+
+    sync_dir(path)?;
+
+    Ok(result)
+}
+
+/// Syncs the directory holding the store, so the rename survives a crash.
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    File::open(dir)?.sync_all()?;
+
+    Ok(())
+}
+
+/// Directories can't be opened as files here; the rename is as durable as
+/// the platform makes it.
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn write(path: &OsStr, store: &Store) -> Result<()> {
+    let mut file = File::create(path)?;
+    serde_json::to_writer_pretty(&mut file, store)?;
+    file.write_all(b"\n")?;
+    file.sync_all()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+
+    use pretty_assertions::assert_eq;
+
+    fn db(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("iprs-{name}-{}.json", std::process::id()));
+        remove_db(&path);
+
+        path
+    }
+
+    fn remove_db(path: &Path) {
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(lock_path(path));
+    }
+
+    fn alloc(name: &str, size: u8) -> AllocArgs {
+        AllocArgs {
+            size,
+            name: name.to_string(),
+            pool: None,
+            owner: Some("ci".to_string()),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn allocates_and_releases_through_the_store() {
+        let path = db("alloc");
+
+        update(&path, |store| {
+            store.add_pool("prod", IpNet::from_str("10.0.0.0/24").unwrap())
+        })
+        .unwrap();
+
+        let first = update(&path, |store| store.allocate(&alloc("web", 26))).unwrap();
+        let second = update(&path, |store| store.allocate(&alloc("db", 25))).unwrap();
+
+        assert_eq!(first.to_string(), "10.0.0.0/26");
+        assert_eq!(second.to_string(), "10.0.0.128/25");
+
+        update(&path, |store| store.release("web")).unwrap();
+        let reused = update(&path, |store| store.allocate(&alloc("cache", 26))).unwrap();
+
+        assert_eq!(reused, first);
+
+        let mut ctx = create_test_ctx();
+        run(
+            &mut ctx,
+            &IpamArgs {
+                db: path.clone(),
+                command: IpamCommand::List,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "prod - 10.0.0.0/24\n  db    - 10.0.0.128/25\n  cache - 10.0.0.0/26\n"
+        );
+
+        remove_db(&path);
+    }
+
+    #[test]
+    fn reading_a_missing_store_creates_no_files() {
+        let path = db("missing");
+
+        assert!(read(&path).unwrap().pools.is_empty());
+        assert!(!lock_path(&path).exists());
+    }
+
+    #[test]
+    fn rejects_duplicate_names_and_overlapping_pools() {
+        let mut store = Store::default();
+
+        store
+            .add_pool("prod", IpNet::from_str("10.0.0.0/16").unwrap())
+            .unwrap();
+        store.allocate(&alloc("web", 24)).unwrap();
+
+        assert!(matches!(
+            store.add_pool("lab", IpNet::from_str("10.0.1.0/24").unwrap()),
+            Err(Error::PoolOverlap(_, _))
+        ));
+        assert!(matches!(
+            store.allocate(&alloc("web", 24)),
+            Err(Error::NameInUse(_))
+        ));
+        assert!(matches!(
+            store.allocate(&alloc("prod", 24)),
+            Err(Error::NameInUse(_))
+        ));
+        assert!(matches!(
+            store.add_pool("web", IpNet::from_str("10.1.0.0/16").unwrap()),
+            Err(Error::NameInUse(_))
+        ));
+    }
+}
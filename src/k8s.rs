@@ -0,0 +1,369 @@
+use clap::Args;
+use ipnet::IpNet;
+use num_traits::cast;
+use std::io::Write;
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::{NetworkDisplay, NetworkPrimitive};
+use crate::parse::parse_network;
+
+/// Shortest service CIDR prefixes kube-apiserver accepts, as it caps the
+/// service range at 2^20 addresses.
+const MIN_SERVICE_PREFIX_V4: u8 = 12;
+const MIN_SERVICE_PREFIX_V6: u8 = 108;
+
+#[derive(Args)]
+pub struct K8sArgs {
+    /// Cluster pod CIDR, or one IPv4 and one IPv6 CIDR for dual-stack
+    #[arg(long, value_delimiter = ',', required = true, value_parser = parse_network)]
+    pod_cidr: Vec<IpNet>,
+    /// Service CIDR, in the same families as the pod CIDR
+    #[arg(long, value_delimiter = ',', required = true, value_parser = parse_network)]
+    service_cidr: Vec<IpNet>,
+    /// Network the nodes themselves are addressed from
+    #[arg(long, value_delimiter = ',', value_parser = parse_network)]
+    node_cidr: Vec<IpNet>,
+    /// Prefix length of each node's IPv4 pod range
+    #[arg(long, default_value_t = 24)]
+    node_mask_size: u8,
+    /// Prefix length of each node's IPv6 pod range
+    #[arg(long, default_value_t = 64)]
+    node_mask_size_v6: u8,
+    /// Number of nodes the cluster is expected to grow to
+    #[arg(long)]
+    nodes: Option<u128>,
+    /// Pods each node runs at most (the kubelet --max-pods)
+    #[arg(long, default_value_t = 110)]
+    max_pods: u128,
+}
+
+/// The networks of one address family of the cluster.
+struct Cidrs<N> {
+    pod: N,
+    service: N,
+    node: Option<N>,
+    mask: u8,
+}
+
+/// Computed figures for display and the problems found with the layout.
+struct Report {
+    fields: Vec<(&'static str, String)>,
+    problems: Vec<String>,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &K8sArgs) -> Result<()> {
+    let pods = by_family(&args.pod_cidr, "pod")?;
+    let services = by_family(&args.service_cidr, "service")?;
+    let nodes = by_family(&args.node_cidr, "node")?;
+
+    if pods.0.is_some() != services.0.is_some() || pods.1.is_some() != services.1.is_some() {
+        return Err(Error::InvalidClusterCidrs(
+            "pod and service CIDRs must cover the same address families".to_string(),
+        ));
+    }
+    if let Some(node) = [(nodes.0, pods.0), (nodes.1, pods.1)]
+        .into_iter()
+        .find_map(|(node, pod)| node.filter(|_| pod.is_none()))
+    {
+        return Err(Error::InvalidClusterCidrs(format!(
+            "node CIDR {node} has no pod CIDR of the same family"
+        )));
+    }
+
+    let mut reports = Vec::new();
+
+    if let (Some(IpNet::V4(pod)), Some(IpNet::V4(service))) = (pods.0, services.0) {
+        let node = match nodes.0 {
+            Some(IpNet::V4(node)) => Some(node),
+            _ => None,
+        };
+        let cidrs = Cidrs {
+            pod: pod.trunc(),
+            service: service.trunc(),
+            node: node.map(|node| node.trunc()),
+            mask: args.node_mask_size,
+        };
+
+        reports.push((
+            pod.trunc().to_string(),
+            "ipv4",
+            check(&cidrs, args, MIN_SERVICE_PREFIX_V4),
+        ));
+    }
+
+    if let (Some(IpNet::V6(pod)), Some(IpNet::V6(service))) = (pods.1, services.1) {
+        let node = match nodes.1 {
+            Some(IpNet::V6(node)) => Some(node),
+            _ => None,
+        };
+        let cidrs = Cidrs {
+            pod: pod.trunc(),
+            service: service.trunc(),
+            node: node.map(|node| node.trunc()),
+            mask: args.node_mask_size_v6,
+        };
+
+        reports.push((
+            pod.trunc().to_string(),
+            "ipv6",
+            check(&cidrs, args, MIN_SERVICE_PREFIX_V6),
+        ));
+    }
+
+    for (i, (pod, version, report)) in reports.iter().enumerate() {
+        if i > 0 {
+            ctx.writeln("\n-")?;
+        }
+
+        ctx.writeln(format!("-[{version} : {pod}] - 0\n"))?;
+        ctx.writeln("[Kubernetes cluster]".to_string())?;
+
+        for (label, value) in &report.fields {
+            ctx.writeln(format!("{label: <24}- {value}"))?;
+        }
+    }
+
+    for (_, _, report) in &reports {
+        for problem in &report.problems {
+            ctx.error_without_exit(problem)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a list of CIDRs into at most one IPv4 and one IPv6 network.
+fn by_family(cidrs: &[IpNet], kind: &str) -> Result<(Option<IpNet>, Option<IpNet>)> {
+    let mut v4 = None;
+    let mut v6 = None;
+
+    for cidr in cidrs {
+        let slot = match cidr {
+            IpNet::V4(_) => &mut v4,
+            IpNet::V6(_) => &mut v6,
+        };
+
+        if slot.replace(*cidr).is_some() {
+            return Err(Error::InvalidClusterCidrs(format!(
+                "more than one {kind} CIDR of the same family"
+            )));
+        }
+    }
+
+    Ok((v4, v6))
+}
+
+/// Hosts usable in `network`, or `None` when the count doesn't fit a u128.
+fn host_count<N: NetworkDisplay>(network: &N) -> Option<u128> {
+    let (first, last) = network.usable_hosts().bounds()?;
+
+    cast::<N::Primitive, u128>(last - first)?.checked_add(1)
+}
+
+fn check<N: NetworkDisplay + Copy>(
+    cidrs: &Cidrs<N>,
+    args: &K8sArgs,
+    min_service_prefix: u8,
+) -> Report {
+    let Cidrs {
+        pod,
+        service,
+        node,
+        mask,
+    } = cidrs;
+    let mut fields = vec![
+        ("Pod CIDR", pod.to_string()),
+        ("Node pod CIDR mask", format!("/{mask}")),
+    ];
+    let mut problems = Vec::new();
+
+    if *mask < pod.prefix_len() || *mask > N::Primitive::BITS {
+        problems.push(format!(
+            "Node mask /{mask} must be between /{} and /{}",
+            pod.prefix_len(),
+            N::Primitive::BITS
+        ));
+    } else {
+        let node_bits = mask - pod.prefix_len();
+        let max_nodes = 1u128.checked_shl(u32::from(node_bits));
+        let node_block = N::from_addr_prefix(pod.primitive_to_addr(pod.network_primitive()), *mask)
+            .expect("mask is a valid prefix length");
+        let pods_per_node = host_count(&node_block);
+
+        fields.push((
+            "Max nodes",
+            max_nodes.map_or_else(|| format!("2^{node_bits}"), |max| max.to_string()),
+        ));
+        fields.push((
+            "Pod addresses per node",
+            pods_per_node.map_or_else(|| node_block.address_count(), |count| count.to_string()),
+        ));
+
+        if pods_per_node.is_some_and(|count| count < args.max_pods) {
+            problems.push(format!(
+                "Nodes get {} pod addresses, fewer than the {} pods they may run",
+                pods_per_node.unwrap_or_default(),
+                args.max_pods
+            ));
+        }
+        if let (Some(nodes), Some(max_nodes)) = (args.nodes, max_nodes) {
+            if nodes > max_nodes {
+                problems.push(format!(
+                    "{nodes} nodes need more than the {max_nodes} /{mask} ranges in {pod}"
+                ));
+            }
+        }
+    }
+
+    fields.push(("Service CIDR", service.to_string()));
+    fields.push(("Service addresses", service.address_count()));
+
+    if service.prefix_len() < min_service_prefix {
+        problems.push(format!(
+            "Service CIDR {service} is larger than the /{min_service_prefix} kube-apiserver allows"
+        ));
+    }
+
+    let mut networks = vec![("pod", pod), ("service", service)];
+
+    if let Some(node) = node {
+        let capacity = host_count(node);
+
+        fields.push(("Node CIDR", node.to_string()));
+        fields.push((
+            "Node addresses",
+            capacity.map_or_else(|| node.address_count(), |count| count.to_string()),
+        ));
+
+        if let (Some(nodes), Some(capacity)) = (args.nodes, capacity) {
+            if nodes > capacity {
+                problems.push(format!("{node} only has addresses for {capacity} nodes"));
+            }
+        }
+
+        networks.push(("node", node));
+    }
+
+    for (i, (kind, network)) in networks.iter().enumerate() {
+        for (other_kind, other) in &networks[i + 1..] {
+            if network.overlaps(other) {
+                problems.push(format!(
+                    "The {kind} CIDR {network} overlaps the {other_kind} CIDR {other}"
+                ));
+            }
+        }
+    }
+
+    Report { fields, problems }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+
+    use pretty_assertions::assert_eq;
+
+    fn nets(cidrs: &[&str]) -> Vec<IpNet> {
+        cidrs
+            .iter()
+            .map(|cidr| IpNet::from_str(cidr).unwrap())
+            .collect()
+    }
+
+    fn args(pods: &[&str], services: &[&str], nodes: &[&str]) -> K8sArgs {
+        K8sArgs {
+            pod_cidr: nets(pods),
+            service_cidr: nets(services),
+            node_cidr: nets(nodes),
+            node_mask_size: 24,
+            node_mask_size_v6: 64,
+            nodes: Some(100),
+            max_pods: 110,
+        }
+    }
+
+    #[test]
+    fn sizes_a_dual_stack_cluster() {
+        let mut ctx = create_test_ctx();
+        let args = args(
+            &["10.244.0.0/16", "fd00:10:244::/56"],
+            &["10.96.0.0/12", "fd00:10:96::/112"],
+            &["192.168.0.0/24"],
+        );
+
+        run(&mut ctx, &args).unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "-[ipv4 : 10.244.0.0/16] - 0
+
+[Kubernetes cluster]
+Pod CIDR                - 10.244.0.0/16
+Node pod CIDR mask      - /24
+Max nodes               - 256
+Pod addresses per node  - 254
+Service CIDR            - 10.96.0.0/12
+Service addresses       - 1048576
+Node CIDR               - 192.168.0.0/24
+Node addresses          - 254
+
+-
+-[ipv6 : fd00:10:244::/56] - 0
+
+[Kubernetes cluster]
+Pod CIDR                - fd00:10:244::/56
+Node pod CIDR mask      - /64
+Max nodes               - 256
+Pod addresses per node  - 18446744073709551616
+Service CIDR            - fd00:10:96::/112
+Service addresses       - 65536
+"
+        );
+        assert!(!ctx.errored);
+    }
+
+    #[test]
+    fn reports_overlaps_and_capacity_problems() {
+        let mut ctx = create_test_ctx();
+        let args = K8sArgs {
+            nodes: Some(2000),
+            node_mask_size: 26,
+            ..args(&["10.0.0.0/16"], &["10.0.128.0/20"], &[])
+        };
+
+        run(&mut ctx, &args).unwrap();
+
+        assert_eq!(
+            String::from_utf8(ctx.err_output().clone()).unwrap(),
+            "Nodes get 62 pod addresses, fewer than the 110 pods they may run
+2000 nodes need more than the 1024 /26 ranges in 10.0.0.0/16
+The pod CIDR 10.0.0.0/16 overlaps the service CIDR 10.0.128.0/20
+"
+        );
+        assert!(ctx.errored);
+    }
+
+    #[test]
+    fn rejects_mismatched_families() {
+        let mut ctx = create_test_ctx();
+        let e = run(
+            &mut ctx,
+            &args(&["10.244.0.0/16", "fd00::/56"], &["10.96.0.0/12"], &[]),
+        )
+        .unwrap_err();
+
+        assert!(matches!(e, Error::InvalidClusterCidrs(_)));
+
+        let e = run(
+            &mut ctx,
+            &args(&["10.244.0.0/16"], &["10.96.0.0/12"], &["fd00::/64"]),
+        )
+        .unwrap_err();
+
+        assert!(matches!(e, Error::InvalidClusterCidrs(_)));
+    }
+}
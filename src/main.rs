@@ -1,57 +1,242 @@
+mod allocate;
+mod conflict;
 mod context;
+mod dhcp;
 mod error;
+mod extract;
+mod firewall;
+mod format;
+mod free;
+mod ifaddr;
 mod interface;
+mod ipam;
+mod k8s;
+mod normalize;
+mod parse;
+mod repl;
 mod rng;
+mod template;
+mod tree;
+mod tui;
+mod ula;
+mod vpc;
+mod wireguard;
 
-use clap::{arg, Parser};
+use clap::{Parser, Subcommand};
 use context::Ctx;
-use error::Result;
-use interface::Interface;
-use ipnet::{IpNet, Ipv4Net, Ipv6Net};
-use std::io::Write;
+use error::{Error, Result};
+use format::OutputFormat;
+use interface::{
+    hosts::HostQuery,
+    traits::{Navigation, SummaryOptions},
+    Interface,
+};
+use ipnet::IpNet;
+use parse::{notation_warning, parse_ip};
+use rng::{DefaultRng, NetworkRng, SeededRng};
+use std::fs;
+use std::io::{IsTerminal, Write};
 use std::net::IpAddr;
+use std::path::PathBuf;
 use std::process;
-use std::str::FromStr;
-
-use crate::error::Error;
+use template::Template;
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
     #[arg(trailing_var_arg(true))]
     ip: Option<Vec<String>>,
     #[arg(short, long)]
     random: bool,
+    /// Seed --random picks so they can be reproduced. The same seed gives the
+    /// same results across iprs releases.
+    #[arg(long, requires = "random")]
+    seed: Option<u64>,
     #[arg(short, long)]
     split: Option<u8>,
+    /// Render each result through a template, e.g. '{network}/{prefix} {broadcast}'
+    #[arg(long, conflicts_with_all = ["random", "hosts", "nth", "template_file"])]
+    template: Option<String>,
+    /// Read the output template from a file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["random", "hosts", "nth"])]
+    template_file: Option<PathBuf>,
+    /// Add the addresses and masks in binary to the summary
+    #[arg(short, long)]
+    binary: bool,
+    /// Output format for split listings
+    #[arg(long, value_enum, default_value_t)]
+    format: OutputFormat,
+    /// Number of distinct, non-overlapping random subnets to pick
+    #[arg(long, default_value_t = 1, requires = "random")]
+    count: usize,
+    /// Existing allocations that random subnets must not overlap
+    #[arg(long, value_delimiter = ',', requires = "random")]
+    avoid: Vec<IpNet>,
+    /// Move forward N blocks of the same size
+    #[arg(long, value_name = "N", group = "navigation")]
+    next: Option<u64>,
+    /// Move back N blocks of the same size
+    #[arg(long, value_name = "N", group = "navigation")]
+    prev: Option<u64>,
+    /// Move to the enclosing network one bit shorter
+    #[arg(long, group = "navigation")]
+    parent: bool,
+    /// Move to the enclosing network with the given prefix length
+    #[arg(long, value_name = "PREFIX", group = "navigation")]
+    supernet: Option<u8>,
+    /// Move to the other half of the parent network
+    #[arg(long, group = "navigation")]
+    sibling: bool,
+    /// List usable host addresses
+    #[arg(long)]
+    hosts: bool,
+    /// Maximum number of hosts to list
+    #[arg(long, requires = "hosts")]
+    limit: Option<u64>,
+    /// Number of hosts to skip before listing
+    #[arg(long, requires = "hosts", default_value_t = 0)]
+    offset: u64,
+    /// Addresses to skip when picking a random host
+    #[arg(long, value_delimiter = ',', requires = "random")]
+    exclude: Vec<IpAddr>,
+    /// Show only the host at this index (0 is the first, -1 the last)
+    #[arg(long, allow_negative_numbers = true, conflicts_with_all = ["limit", "offset"])]
+    nth: Option<i128>,
 }
 
-fn parse_ip(ip: &str) -> Result<IpNet> {
-    let parsed_ip = match IpAddr::from_str(ip) {
-        Ok(IpAddr::V4(ipv4)) => IpNet::V4(Ipv4Net::new(ipv4, 32)?),
-        Ok(IpAddr::V6(ipv6)) => IpNet::V6(Ipv6Net::new(ipv6, 128)?),
-        Err(_) => {
-            if let Ok(ipv4) = Ipv4Net::from_str(ip) {
-                IpNet::V4(ipv4)
-            } else if let Ok(ipv6) = Ipv6Net::from_str(ip) {
-                IpNet::V6(ipv6)
-            } else {
-                return Err(Error::AddrParse(ip.to_string()));
+#[derive(Subcommand)]
+enum Command {
+    /// Deterministically allocate a subnet per key by hashing it
+    Allocate(allocate::AllocateArgs),
+    /// Check networks against the local routing table and interface addresses
+    CheckConflict(conflict::CheckConflictArgs),
+    /// Generate ISC dhcpd, Kea or dnsmasq pool configuration for a network
+    Dhcp(dhcp::DhcpArgs),
+    /// Find the addresses and networks mentioned in logs or other text
+    Extract(extract::ExtractArgs),
+    /// Generate nftables, iptables, ipset or Cisco ACL rules for prefixes
+    Firewall(firewall::FirewallArgs),
+    /// List the free blocks in a network, or find free subnets of a given size
+    Free(free::FreeArgs),
+    /// Manage pools and named allocations in a local JSON database
+    Ipam(ipam::IpamArgs),
+    /// Size and validate the pod, service and node CIDRs of a Kubernetes cluster
+    K8s(k8s::K8sArgs),
+    /// Truncate host bits, sort numerically and remove duplicates from a list of networks
+    Normalize(normalize::NormalizeArgs),
+    /// Draw a network and its allocations as a tree, with the free gaps between them
+    Tree(tree::TreeArgs),
+    /// Explore a network by drilling down through its splits and mark allocations
+    Tui(tui::TuiArgs),
+    /// Generate an RFC 4193 Unique Local IPv6 /48 prefix
+    Ula(ula::UlaArgs),
+    /// Plan per-zone subnet tiers for an AWS, Azure or GCP VPC
+    Vpc(vpc::VpcArgs),
+    /// Assign WireGuard peers stable tunnel addresses and write their configs
+    Wireguard(wireguard::WireguardArgs),
+}
+
+impl Cli {
+    fn navigation(&self) -> Option<Navigation> {
+        if let Some(steps) = self.next {
+            Some(Navigation::Next(steps))
+        } else if let Some(steps) = self.prev {
+            Some(Navigation::Prev(steps))
+        } else if self.parent {
+            Some(Navigation::Parent)
+        } else if let Some(prefix_len) = self.supernet {
+            Some(Navigation::Supernet(prefix_len))
+        } else if self.sibling {
+            Some(Navigation::Sibling)
+        } else {
+            None
+        }
+    }
+
+    fn template(&self) -> Result<Option<Template>> {
+        let template = match (&self.template, &self.template_file) {
+            (Some(template), _) => template.clone(),
+            (None, Some(path)) => {
+                let template = fs::read_to_string(path)?;
+
+                template.strip_suffix('\n').unwrap_or(&template).to_string()
             }
+            (None, None) => return Ok(None),
+        };
+
+        Ok(Some(Template::parse(&template)?))
+    }
+
+    fn host_query(&self) -> Option<HostQuery> {
+        if let Some(index) = self.nth {
+            Some(HostQuery::Nth(index))
+        } else if self.hosts {
+            Some(HostQuery::Page {
+                offset: self.offset,
+                limit: self.limit,
+            })
+        } else {
+            None
         }
-    };
+    }
+}
+
+/// Runs one invocation, returning the last network shown so the REPL can refer back to it.
+fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: Cli) -> Result<Option<IpNet>> {
+    if let Some(command) = &args.command {
+        match command {
+            Command::Allocate(allocate_args) => allocate::run(ctx, allocate_args)?,
+            Command::CheckConflict(conflict_args) => conflict::run(ctx, conflict_args)?,
+            Command::Dhcp(dhcp_args) => dhcp::run(ctx, dhcp_args)?,
+            Command::Extract(extract_args) => extract::run(ctx, extract_args)?,
+            Command::Firewall(firewall_args) => firewall::run(ctx, firewall_args)?,
+            Command::Free(free_args) => free::run(ctx, free_args)?,
+            Command::Ipam(ipam_args) => ipam::run(ctx, ipam_args)?,
+            Command::K8s(k8s_args) => k8s::run(ctx, k8s_args)?,
+            Command::Normalize(normalize_args) => normalize::run(ctx, normalize_args)?,
+            Command::Tree(tree_args) => tree::run(ctx, tree_args)?,
+            Command::Tui(tui_args) => tui::run(ctx, tui_args)?,
+            Command::Ula(ula_args) => ula::run(ctx, ula_args)?,
+            Command::Vpc(vpc_args) => vpc::run(ctx, vpc_args)?,
+            Command::Wireguard(wireguard_args) => wireguard::run(ctx, wireguard_args)?,
+        }
+
+        return Ok(None);
+    }
 
-    Ok(parsed_ip)
+    match args.seed {
+        Some(seed) => run_with_rng(ctx, &args, &mut SeededRng::new(seed)),
+        None => run_with_rng(ctx, &args, &mut DefaultRng),
+    }
 }
 
-fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: Cli) -> Result<()> {
+fn run_with_rng<W: Write, E: Write, R: NetworkRng>(
+    ctx: &mut Ctx<W, E>,
+    args: &Cli,
+    rng: &mut R,
+) -> Result<Option<IpNet>> {
     let ip_inputs = match &args.ip {
         Some(ips) => ips,
-        None => ctx.error_and_exit("No IP subnet supplied"),
+        None => return Err(Error::NoInput),
     };
+    let template = args.template()?;
+    let mut last = None;
+
+    if args.split.is_some() && !args.random && template.is_none() && args.host_query().is_none() {
+        if let Some(header) = args.format.header() {
+            ctx.writeln(header)?;
+        }
+    }
 
     for ip_input in ip_inputs.iter() {
-        let interface = match parse_ip(ip_input) {
-            Ok(ip) => ip,
+        if let Some(warning) = notation_warning(ip_input) {
+            ctx.ewriteln(warning)?;
+        }
+
+        let interfaces = match parse_ip(ip_input) {
+            Ok(ips) => ips,
             Err(e) => {
                 ctx.error_without_exit(e)?;
 
@@ -59,34 +244,108 @@ fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: Cli) -> Result<()> {
             }
         };
 
-        if args.random && args.split.is_none() {
-            ctx.error_and_exit("--random requires --split");
+        for interface in interfaces {
+            if let Some(shown) = run_interface(ctx, args, interface, template.as_ref(), rng)? {
+                last = Some(shown);
+            }
         }
+    }
 
-        if let Some(split) = args.split {
-            if args.random {
-                interface.random_split(ctx, split)?;
-            } else {
-                match interface.split(ctx, split) {
-                    Ok(()) => {}
-                    Err(e) => {
-                        ctx.error_without_exit(e)?;
-
-                        continue;
-                    }
-                }
+    Ok(last)
+}
+
+fn run_interface<W: Write, E: Write, R: NetworkRng>(
+    ctx: &mut Ctx<W, E>,
+    args: &Cli,
+    interface: IpNet,
+    template: Option<&Template>,
+    rng: &mut R,
+) -> Result<Option<IpNet>> {
+    if args.random && args.split.is_none() && !args.hosts {
+        return Err(Error::RandomWithoutSplit);
+    }
+
+    let interface = match args.navigation() {
+        Some(navigation) => match interface.navigate(navigation) {
+            Ok(interface) => interface,
+            Err(e) => {
+                ctx.error_without_exit(e)?;
+
+                return Ok(None);
             }
-        } else {
-            interface.summarize(ctx)?;
+        },
+        None => interface,
+    };
+
+    if let Some(template) = template {
+        if let Err(e) = interface.render_template(ctx, template, args.split) {
+            ctx.error_without_exit(e)?;
+
+            return Ok(None);
+        }
+
+        return Ok(Some(interface));
+    }
+
+    if args.random && args.hosts {
+        if let Err(e) = interface.random_host(ctx, &args.exclude, rng) {
+            ctx.error_without_exit(e)?;
+
+            return Ok(None);
+        }
+    } else if let Some(query) = args.host_query() {
+        if let Err(e) = interface.list_hosts(ctx, query) {
+            ctx.error_without_exit(e)?;
+
+            return Ok(None);
         }
+    } else if let Some(split) = args.split {
+        if args.random {
+            if let Err(e) = interface.random_split(
+                ctx,
+                split,
+                args.count,
+                &args.avoid,
+                rng,
+                SummaryOptions {
+                    binary: args.binary,
+                },
+            ) {
+                ctx.error_without_exit(e)?;
+
+                return Ok(None);
+            }
+        } else {
+            match interface.split(ctx, split, args.format) {
+                // Delimited output is meant for other tools, so skip the separator
+                Ok(()) if args.format.delimiter().is_some() => return Ok(Some(interface)),
+                Ok(()) => {}
+                Err(e) => {
+                    ctx.error_without_exit(e)?;
 
-        ctx.writeln("\n-")?;
+                    return Ok(None);
+                }
+            }
+        }
+    } else {
+        interface.summarize(
+            ctx,
+            SummaryOptions {
+                binary: args.binary,
+            },
+        )?;
     }
 
-    Ok(())
+    ctx.writeln("\n-")?;
+
+    Ok(Some(interface))
 }
 
 fn main() -> Result<()> {
+    if std::env::args_os().len() == 1 && std::io::stdin().is_terminal() {
+        return repl::run();
+    }
+
     let args = Cli::parse();
     let mut ctx = Ctx::new(std::io::stdout().lock(), std::io::stderr().lock());
 
@@ -95,3 +354,115 @@ fn main() -> Result<()> {
         Err(e) => ctx.error_and_exit(e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+    use crate::interface::traits::NetworkDisplay;
+    use crate::template::SummaryField;
+    use ipnet::{Ipv4Net, Ipv6Net};
+
+    fn run_args(args: &[&str]) -> String {
+        let mut ctx = create_test_ctx();
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        run(&mut ctx, cli).unwrap();
+
+        get_output_as_string(&ctx)
+    }
+
+    #[test]
+    fn next_and_prev_take_a_count() {
+        assert!(
+            run_args(&["iprs", "--next", "3", "10.0.0.0/24"]).contains("-[ipv4 : 10.0.3.0/24] - 0")
+        );
+        assert!(
+            run_args(&["iprs", "--prev=2", "10.0.4.0/24"]).contains("-[ipv4 : 10.0.2.0/24] - 0")
+        );
+        assert!(
+            run_args(&["iprs", "--next", "1", "167837953"]).contains("-[ipv4 : 10.1.1.2/32] - 0")
+        );
+        assert!(Cli::try_parse_from(["iprs", "--next", "10.0.0.0/24"]).is_err());
+    }
+
+    #[test]
+    fn writes_one_csv_header_for_all_inputs() {
+        assert_eq!(
+            run_args(&[
+                "iprs",
+                "-s",
+                "25",
+                "--format",
+                "csv",
+                "10.0.0.0/24",
+                "10.1.0.0/24"
+            ]),
+            "index,cidr,network,last_address,first_usable,last_usable,size
+0,10.0.0.0/25,10.0.0.0,10.0.0.127,10.0.0.1,10.0.0.126,128
+1,10.0.0.128/25,10.0.0.128,10.0.0.255,10.0.0.129,10.0.0.254,128
+0,10.1.0.0/25,10.1.0.0,10.1.0.127,10.1.0.1,10.1.0.126,128
+1,10.1.0.128/25,10.1.0.128,10.1.0.255,10.1.0.129,10.1.0.254,128
+"
+        );
+    }
+
+    #[test]
+    fn templates_accept_the_same_fields_for_both_families() {
+        let template = "{cidr} {host_decimal} {host_octal} {wildcard} {first_usable} {last_usable} {size} {type}";
+
+        assert_eq!(
+            run_args(&["iprs", "--template", template, "10.0.0.1/30", "2001:db8::1/126"]),
+            "10.0.0.1/30 167772161 01200000001 0.0.0.3 10.0.0.1 10.0.0.2 4 N/A
+2001:db8::1/126 42540766411282592856903984951653826561 N/A ::3 2001:db8:: 2001:db8::3 4 Aggregatable Global Unicast Addresses
+"
+        );
+        assert_eq!(
+            run_args(&["iprs", "--template", "{size}", "0.0.0.0/0"]),
+            "4294967296\n"
+        );
+
+        let keys = |fields: Vec<SummaryField>| {
+            let mut keys: Vec<&str> = fields.iter().map(|field| field.key).collect();
+            keys.sort();
+            keys
+        };
+
+        assert_eq!(
+            keys(Ipv4Net::default().summary_fields()),
+            keys(Ipv6Net::default().summary_fields())
+        );
+    }
+
+    #[test]
+    fn binary_applies_to_random_splits_and_ula() {
+        assert!(
+            run_args(&["iprs", "-r", "-b", "-s", "26", "--seed", "1", "10.0.0.0/24"])
+                .contains("[CIDR bitmaps]")
+        );
+        assert!(run_args(&["iprs", "ula", "--seed", "1", "-b"]).contains("[IPV6 BITMAPS]"));
+    }
+
+    #[test]
+    fn failed_random_split_does_not_stop_later_inputs() {
+        let mut ctx = create_test_ctx();
+        let cli = Cli::try_parse_from([
+            "iprs",
+            "-r",
+            "-s",
+            "25",
+            "--avoid",
+            "10.0.0.0/24",
+            "--seed",
+            "1",
+            "10.0.0.0/24",
+            "10.0.1.0/24",
+        ])
+        .unwrap();
+
+        run(&mut ctx, cli).unwrap();
+
+        assert!(ctx.errored);
+        assert!(get_output_as_string(&ctx).contains("-[ipv4 : 10.0.1."));
+    }
+}
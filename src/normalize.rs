@@ -0,0 +1,129 @@
+use clap::Args;
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::io::{self, BufRead, Write};
+
+use crate::context::Ctx;
+use crate::error::Result;
+use crate::interface::traits::NetworkCore;
+use crate::parse::parse_network;
+
+#[derive(Args)]
+pub struct NormalizeArgs {
+    /// Networks to normalise, read one per line from stdin when none are given
+    networks: Vec<String>,
+    /// Warn about networks that had host bits set
+    #[arg(short, long)]
+    warn: bool,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &NormalizeArgs) -> Result<()> {
+    let inputs = if args.networks.is_empty() {
+        io::stdin().lock().lines().collect::<io::Result<Vec<_>>>()?
+    } else {
+        args.networks.clone()
+    };
+
+    normalize(ctx, &inputs, args.warn)
+}
+
+fn normalize<W: Write, E: Write>(ctx: &mut Ctx<W, E>, inputs: &[String], warn: bool) -> Result<()> {
+    let mut ipv4: Vec<Ipv4Net> = Vec::new();
+    let mut ipv6: Vec<Ipv6Net> = Vec::new();
+
+    for input in inputs.iter().map(|input| input.trim()) {
+        if input.is_empty() || input.starts_with('#') {
+            continue;
+        }
+
+        let network = match parse_network(input) {
+            Ok(network) => network,
+            Err(e) => {
+                ctx.error_without_exit(e)?;
+
+                continue;
+            }
+        };
+
+        if warn && network.addr() != network.network() {
+            ctx.ewriteln(format!(
+                "{input} has host bits set, using {}",
+                network.trunc()
+            ))?;
+        }
+
+        match network.trunc() {
+            IpNet::V4(network) => ipv4.push(network),
+            IpNet::V6(network) => ipv6.push(network),
+        }
+    }
+
+    for network in sorted(ipv4) {
+        ctx.writeln(network)?;
+    }
+    for network in sorted(ipv6) {
+        ctx.writeln(network)?;
+    }
+
+    Ok(())
+}
+
+/// Orders by network address then prefix length, dropping duplicates.
+fn sorted<N: NetworkCore + PartialEq>(mut networks: Vec<N>) -> Vec<N> {
+    networks.sort_by_key(|network| (network.network_primitive(), network.prefix_len()));
+    networks.dedup();
+
+    networks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+
+    use pretty_assertions::assert_eq;
+
+    fn inputs(inputs: &[&str]) -> Vec<String> {
+        inputs.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn sorts_numerically_and_removes_duplicates() {
+        let mut ctx = create_test_ctx();
+
+        normalize(
+            &mut ctx,
+            &inputs(&[
+                "2001:DB8:0:0::/32",
+                "10.0.0.0/8",
+                "9.0.0.0/8",
+                "# comment",
+                "10.0.0.0/16",
+                "",
+                "10.1.2.3/8",
+                "192.168.0.1",
+            ]),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "9.0.0.0/8\n10.0.0.0/8\n10.0.0.0/16\n192.168.0.1/32\n2001:db8::/32\n"
+        );
+        assert!(!ctx.errored);
+    }
+
+    #[test]
+    fn warns_about_host_bits_and_reports_bad_input() {
+        let mut ctx = create_test_ctx();
+
+        normalize(&mut ctx, &inputs(&["10.1.2.3/8", "nonsense"]), true).unwrap();
+
+        assert_eq!(get_output_as_string(&ctx), "10.0.0.0/8\n");
+        assert_eq!(
+            String::from_utf8(ctx.err_output().clone()).unwrap(),
+            "10.1.2.3/8 has host bits set, using 10.0.0.0/8\nFailed to parse `nonsense` as interface\n"
+        );
+        assert!(ctx.errored);
+    }
+}
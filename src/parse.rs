@@ -0,0 +1,194 @@
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use std::net::{IpAddr, Ipv4Addr};
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::ifaddr;
+
+/// Parses an address or CIDR in any notation, falling back to the addresses of
+/// a local interface.
+pub fn parse_ip(ip: &str) -> Result<Vec<IpNet>> {
+    match parse_any_notation(ip) {
+        Ok(network) => Ok(vec![network]),
+        Err(_) => parse_interface_name(ip),
+    }
+}
+
+/// Parses an address or CIDR like `parse_network`, also accepting the legacy
+/// integer, hex and octal IPv4 notation. Option values stay strict, so that a
+/// stray count such as `3` isn't read as 0.0.0.3.
+pub fn parse_any_notation(ip: &str) -> Result<IpNet> {
+    // Checked first, as `Ipv4Net` reads leading zeros as decimal
+    match parse_ipv4_notation(ip) {
+        Some(ipv4) => Ok(IpNet::V4(ipv4)),
+        None => parse_network(ip),
+    }
+}
+
+/// Parses an address (as a host network) or CIDR.
+pub fn parse_network(ip: &str) -> Result<IpNet> {
+    let parsed_ip = match IpAddr::from_str(ip) {
+        Ok(IpAddr::V4(ipv4)) => IpNet::V4(Ipv4Net::new(ipv4, 32)?),
+        Ok(IpAddr::V6(ipv6)) => IpNet::V6(Ipv6Net::new(ipv6, 128)?),
+        Err(_) => {
+            if let Ok(ipv4) = Ipv4Net::from_str(ip) {
+                IpNet::V4(ipv4)
+            } else if let Ok(ipv6) = Ipv6Net::from_str(ip) {
+                IpNet::V6(ipv6)
+            } else {
+                return Err(Error::AddrParse(ip.to_string()));
+            }
+        }
+    };
+
+    Ok(parsed_ip)
+}
+
+/// The kind of non-standard IPv4 notation an input was written in.
+#[derive(Debug, PartialEq, Eq)]
+enum Notation {
+    /// `0x0A010101` or `0x0A.0x01.0x01.0x01`
+    Hex,
+    /// `012.1.1.1` or `01200200401`, which is read as decimal by some tools
+    Octal,
+    /// `167837953`
+    Integer,
+}
+
+/// Parses a single number of a legacy address in decimal, `0x` hex or `0` octal.
+fn parse_number(part: &str) -> Option<(u32, Option<Notation>)> {
+    if let Some(hex) = part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+        Some((u32::from_str_radix(hex, 16).ok()?, Some(Notation::Hex)))
+    } else if part.len() > 1 && part.starts_with('0') {
+        Some((
+            u32::from_str_radix(&part[1..], 8).ok()?,
+            Some(Notation::Octal),
+        ))
+    } else {
+        Some((part.parse().ok()?, None))
+    }
+}
+
+/// Parses an IPv4 address written as one 32-bit number or as four octets in
+/// decimal, hex or octal, the way `inet_aton` does.
+fn parse_ipv4_legacy(addr: &str) -> Option<(Ipv4Addr, Notation)> {
+    if !addr.contains('.') {
+        let (value, notation) = parse_number(addr)?;
+
+        return Some((Ipv4Addr::from(value), notation.unwrap_or(Notation::Integer)));
+    }
+
+    let parts = addr
+        .split('.')
+        .map(parse_number)
+        .collect::<Option<Vec<_>>>()?;
+
+    if parts.len() != 4 || parts.iter().any(|(octet, _)| *octet > 255) {
+        return None;
+    }
+
+    let notations: Vec<_> = parts
+        .iter()
+        .filter_map(|(_, notation)| notation.as_ref())
+        .collect();
+    let notation = if notations.contains(&&Notation::Octal) {
+        Notation::Octal
+    } else if notations.contains(&&Notation::Hex) {
+        Notation::Hex
+    } else {
+        return None;
+    };
+    let octets = parts.iter().fold(0, |addr, (octet, _)| (addr << 8) | octet);
+
+    Some((Ipv4Addr::from(octets), notation))
+}
+
+fn parse_ipv4_notation(ip: &str) -> Option<Ipv4Net> {
+    let (addr, prefix_len) = match ip.split_once('/') {
+        Some((addr, prefix_len)) => (addr, prefix_len.parse().ok()?),
+        None => (ip, 32),
+    };
+    let (addr, _) = parse_ipv4_legacy(addr)?;
+
+    Ipv4Net::new(addr, prefix_len).ok()
+}
+
+/// A warning for inputs whose meaning depends on the tool reading them, such as
+/// octets with leading zeros or bare integers.
+pub fn notation_warning(ip: &str) -> Option<String> {
+    let addr = ip.split_once('/').map_or(ip, |(addr, _)| addr);
+    let (parsed, notation) = parse_ipv4_legacy(addr)?;
+
+    match notation {
+        Notation::Octal => Some(format!(
+            "Warning: reading `{addr}` as {parsed}; numbers with a leading zero are octal"
+        )),
+        Notation::Integer => Some(format!(
+            "Warning: reading `{addr}` as {parsed}; a bare number is a 32-bit address"
+        )),
+        Notation::Hex => None,
+    }
+}
+
+/// Falls back to the addresses configured on a local interface, e.g. `eth0`.
+fn parse_interface_name(name: &str) -> Result<Vec<IpNet>> {
+    let networks = ifaddr::interface_networks(name)?;
+
+    if networks.is_empty() {
+        return Err(Error::AddrParse(name.to_string()));
+    }
+
+    Ok(networks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_integer_hex_and_octal_notation() {
+        for input in [
+            "167837953",
+            "0x0A010101",
+            "0x0A.0x01.0x01.0x01",
+            "012.1.1.1",
+            "01200200401",
+        ] {
+            assert_eq!(
+                parse_any_notation(input).unwrap().to_string(),
+                "10.1.1.1/32"
+            );
+        }
+
+        assert_eq!(
+            parse_any_notation("012.1.1.0/24").unwrap().to_string(),
+            "10.1.1.0/24"
+        );
+        assert!(parse_any_notation("09.1.1.1").is_err());
+        assert!(parse_any_notation("256.1.1.1").is_err());
+        assert!(parse_any_notation("4294967296").is_err());
+    }
+
+    #[test]
+    fn option_values_only_accept_standard_notation() {
+        assert!(parse_network("3").is_err());
+        assert!(parse_network("0x0A010101").is_err());
+        assert_eq!(
+            parse_network("10.1.1.0/24").unwrap().to_string(),
+            "10.1.1.0/24"
+        );
+    }
+
+    #[test]
+    fn warns_about_ambiguous_notation() {
+        assert_eq!(
+            notation_warning("012.1.1.1/24").unwrap(),
+            "Warning: reading `012.1.1.1` as 10.1.1.1; numbers with a leading zero are octal"
+        );
+        assert!(notation_warning("167837953").is_some());
+        assert_eq!(notation_warning("0x0A010101"), None);
+        assert_eq!(notation_warning("10.1.1.1"), None);
+    }
+}
@@ -0,0 +1,192 @@
+use clap::{CommandFactory, Parser};
+use ipnet::IpNet;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::io::Write;
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::{run as run_cli, Cli};
+
+const PROMPT: &str = "iprs> ";
+/// Replaced with the last network shown, e.g. `$_ next 1`.
+const LAST_RESULT: &str = "$_";
+
+/// Reads invocations from the terminal until `exit`, `quit` or end of input.
+pub fn run() -> Result<()> {
+    let mut editor = DefaultEditor::new()?;
+    let mut last = None;
+
+    loop {
+        let line = match editor.readline(PROMPT) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+        if line == "exit" || line == "quit" {
+            break;
+        }
+
+        editor.add_history_entry(line)?;
+
+        let mut ctx = Ctx::new(std::io::stdout().lock(), std::io::stderr().lock());
+        eval(&mut ctx, line, &mut last)?;
+    }
+
+    Ok(())
+}
+
+/// Runs one line, reporting its errors without leaving the REPL.
+pub fn eval<W: Write, E: Write>(
+    ctx: &mut Ctx<W, E>,
+    line: &str,
+    last: &mut Option<IpNet>,
+) -> Result<()> {
+    let words = match expand(line, last.as_ref()) {
+        Ok(words) => words,
+        Err(e) => return ctx.error_without_exit(e).map_err(Error::from),
+    };
+
+    let args = match Cli::try_parse_from(std::iter::once("iprs".to_string()).chain(words)) {
+        Ok(args) => args,
+        Err(e) if e.use_stderr() => return ctx.error_without_exit(e.render()).map_err(Error::from),
+        Err(e) => return ctx.writeln(e.render()).map_err(Error::from),
+    };
+
+    match run_cli(ctx, args) {
+        Ok(Some(shown)) => *last = Some(shown),
+        Ok(None) => {}
+        Err(e) => ctx.error_without_exit(e)?,
+    }
+
+    Ok(())
+}
+
+/// Splits a line into CLI arguments.
+///
+/// `$_` becomes the last result, and flags may be written as bare words
+/// (`split 24`). Options are moved in front of the addresses, since everything
+/// after the first address is read as another address.
+fn expand(line: &str, last: Option<&IpNet>) -> Result<Vec<String>> {
+    let words = shlex::split(line).ok_or_else(|| Error::UnbalancedQuotes(line.to_string()))?;
+    let words = words
+        .into_iter()
+        .map(|word| match (word == LAST_RESULT, last) {
+            (true, Some(last)) => Ok(last.to_string()),
+            (true, None) => Err(Error::NoPreviousResult),
+            (false, _) => Ok(word),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let command = Cli::command();
+
+    if let Some(first) = words.first() {
+        if command.find_subcommand(first).is_some() {
+            return Ok(words);
+        }
+    }
+
+    let mut options = Vec::new();
+    let mut addresses = Vec::new();
+    let mut words = words.into_iter();
+
+    while let Some(word) = words.next() {
+        let (name, value) = match word.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (word.as_str(), None),
+        };
+        let name = name.trim_start_matches('-');
+        let arg = command.get_arguments().find(|arg| {
+            !arg.is_positional()
+                && (arg.get_long() == Some(name)
+                    || (word.starts_with('-')
+                        && name.len() == 1
+                        && arg.get_short() == name.chars().next()))
+        });
+
+        match arg {
+            Some(arg) => {
+                let long = arg.get_long().unwrap_or(name);
+
+                match value {
+                    Some(value) => options.push(format!("--{long}={value}")),
+                    None => options.push(format!("--{long}")),
+                }
+
+                if value.is_none() && arg.get_action().takes_values() {
+                    options.extend(words.next());
+                }
+            }
+            None if name == "help" => options.push("--help".to_string()),
+            None => addresses.push(word),
+        }
+    }
+
+    options.extend(addresses);
+
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn expands_bare_flags_and_last_result() {
+        let last = IpNet::from_str("10.0.0.0/24").unwrap();
+
+        assert_eq!(
+            expand("$_ next 1", Some(&last)).unwrap(),
+            ["--next", "1", "10.0.0.0/24"]
+        );
+        assert_eq!(
+            expand(
+                "10.0.0.0/16 split 24 -r --template '{network} {prefix}'",
+                None
+            )
+            .unwrap(),
+            [
+                "--split",
+                "24",
+                "--random",
+                "--template",
+                "{network} {prefix}",
+                "10.0.0.0/16"
+            ]
+        );
+        assert_eq!(
+            expand("next 3 split 26 10.0.0.0/24", None).unwrap(),
+            ["--next", "3", "--split", "26", "10.0.0.0/24"]
+        );
+        assert_eq!(
+            expand("nth=-1 hosts 10.0.0.0/30", None).unwrap(),
+            ["--nth=-1", "--hosts", "10.0.0.0/30"]
+        );
+        assert!(matches!(expand("$_", None), Err(Error::NoPreviousResult)));
+    }
+
+    #[test]
+    fn chains_results_between_lines() {
+        let mut ctx = create_test_ctx();
+        let mut last = None;
+
+        eval(&mut ctx, "10.0.0.0/24", &mut last).unwrap();
+        eval(&mut ctx, "$_ next=2", &mut last).unwrap();
+        eval(&mut ctx, "split 26", &mut last).unwrap();
+
+        assert_eq!(last, Some(IpNet::from_str("10.0.2.0/24").unwrap()));
+        assert!(get_output_as_string(&ctx).contains("-[ipv4 : 10.0.2.0/24] - 0"));
+        assert!(ctx.errored);
+    }
+}
@@ -1,11 +1,19 @@
-use num_traits::PrimInt;
+use num_traits::{cast, PrimInt, Unsigned};
 use rand::distr::uniform::SampleUniform;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha1::{Digest, Sha1};
 use std::ops::Range;
 
 pub trait RandomRangeGenerator<T> {
     fn random_range(&mut self, range: Range<T>) -> T;
 }
 
+/// Generator usable for both IPv4 and IPv6 networks.
+pub trait NetworkRng: RandomRangeGenerator<u32> + RandomRangeGenerator<u128> {}
+
+impl<R> NetworkRng for R where R: RandomRangeGenerator<u32> + RandomRangeGenerator<u128> {}
+
 pub struct DefaultRng;
 
 impl<T> RandomRangeGenerator<T> for DefaultRng
@@ -16,3 +24,169 @@ where
         rand::random_range(range)
     }
 }
+
+/// Reproducible generator for `--seed`.
+///
+/// The same seed yields the same values across iprs releases: the ChaCha20
+/// stream is fixed by its specification, and values are mapped onto ranges by
+/// [`sample_range`] rather than `rand`'s samplers, which may change between
+/// `rand` versions.
+pub struct SeededRng(ChaCha20Rng);
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        let mut key = [0u8; 32];
+        key[..8].copy_from_slice(&seed.to_le_bytes());
+
+        SeededRng(ChaCha20Rng::from_seed(key))
+    }
+}
+
+impl<T> RandomRangeGenerator<T> for SeededRng
+where
+    T: PrimInt + Unsigned,
+{
+    fn random_range(&mut self, range: Range<T>) -> T {
+        sample_range(range, || {
+            (u128::from(self.0.next_u64()) << 64) | u128::from(self.0.next_u64())
+        })
+    }
+}
+
+/// Deterministic generator derived from a string key, for name-based allocation.
+///
+/// Draw `n` is taken from SHA-1(key || n), so a key always produces the same
+/// sequence across iprs releases.
+pub struct HashRng {
+    key: Vec<u8>,
+    counter: u64,
+}
+
+impl HashRng {
+    pub fn new(key: &str) -> Self {
+        HashRng {
+            key: key.as_bytes().to_vec(),
+            counter: 0,
+        }
+    }
+
+    fn next_u128(&mut self) -> u128 {
+        let mut hasher = Sha1::new();
+        hasher.update(&self.key);
+        hasher.update(self.counter.to_be_bytes());
+        self.counter += 1;
+
+        let digest = hasher.finalize();
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&digest[..16]);
+
+        u128::from_be_bytes(bytes)
+    }
+}
+
+impl<T> RandomRangeGenerator<T> for HashRng
+where
+    T: PrimInt + Unsigned,
+{
+    fn random_range(&mut self, range: Range<T>) -> T {
+        sample_range(range, || self.next_u128())
+    }
+}
+
+/// Draws uniformly from `0..=last`, including when `last` is the largest value
+/// of `T` and the range can't be written as a `Range`.
+pub fn random_up_to<T, R>(rng: &mut R, last: T) -> T
+where
+    T: PrimInt,
+    R: RandomRangeGenerator<T> + ?Sized,
+{
+    if last < T::max_value() {
+        return rng.random_range(T::zero()..last + T::one());
+    }
+
+    // Every value of T, drawn as two independent halves of its bits
+    let half = T::zero().count_zeros() as usize / 2;
+    let high = rng.random_range(T::zero()..T::one() << half);
+    let low = rng.random_range(T::zero()..T::one() << half);
+
+    (high << half) | low
+}
+
+/// Uniformly maps random `u128`s from `next` onto `range`.
+///
+/// Each draw keeps the top bits that fit `T`, and draws from the uneven tail
+/// that would bias the modulo are rejected.
+pub fn sample_range<T, F>(range: Range<T>, mut next: F) -> T
+where
+    T: PrimInt + Unsigned,
+    F: FnMut() -> u128,
+{
+    assert!(range.start < range.end, "cannot sample empty range");
+
+    let bits = T::zero().count_zeros();
+    let span = range.end - range.start;
+    // 2^bits mod span, computed without overflowing T
+    let remainder = (T::max_value() % span + T::one()) % span;
+
+    loop {
+        let value: T = cast(next() >> (128 - bits)).expect("value fits in the primitive");
+
+        if value <= T::max_value() - remainder {
+            return range.start + value % span;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn seeded_rng_is_reproducible() {
+        let mut rng = SeededRng::new(42);
+        let values: Vec<u32> = (0..3).map(|_| rng.random_range(0..1000)).collect();
+
+        assert_eq!(values, [209, 493, 633]);
+
+        let mut rng = SeededRng::new(42);
+        let wide: u128 = rng.random_range(0..u128::MAX);
+
+        assert_eq!(wide, 142077028193703520781629116769838521442);
+    }
+
+    #[test]
+    fn hash_rng_depends_only_on_the_key() {
+        let first: u32 = HashRng::new("team-payments").random_range(0..1 << 16);
+        let again: u32 = HashRng::new("team-payments").random_range(0..1 << 16);
+        let other: u32 = HashRng::new("team-search").random_range(0..1 << 16);
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn random_up_to_reaches_the_largest_value() {
+        let mut rng = SeededRng::new(7);
+        let draws: Vec<u8> = (0..4096).map(|_| random_up_to(&mut rng, u8::MAX)).collect();
+
+        assert!(draws.contains(&0));
+        assert!(draws.contains(&u8::MAX));
+
+        let mut rng = SeededRng::new(7);
+        let small: Vec<u8> = (0..64).map(|_| random_up_to(&mut rng, 2)).collect();
+
+        assert!(small.iter().all(|draw| *draw <= 2));
+        assert!(small.contains(&2));
+    }
+
+    #[test]
+    fn sample_range_rejects_the_biased_tail() {
+        let mut draws = [u128::MAX, 5 << 96].into_iter();
+
+        let value: u32 = sample_range(10..16, || draws.next().unwrap());
+
+        assert_eq!(value, 15);
+    }
+}
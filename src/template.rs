@@ -0,0 +1,147 @@
+use crate::error::{Error, Result};
+
+/// A named value from a network summary.
+///
+/// Fields with a label appear in the text summary; every field can be used in
+/// a template by its key.
+pub struct SummaryField {
+    pub key: &'static str,
+    pub label: Option<&'static str>,
+    pub value: String,
+}
+
+impl SummaryField {
+    pub fn new<T: ToString>(key: &'static str, label: &'static str, value: T) -> Self {
+        SummaryField {
+            key,
+            label: Some(label),
+            value: value.to_string(),
+        }
+    }
+
+    /// A field only available to templates.
+    pub fn hidden<T: ToString>(key: &'static str, value: T) -> Self {
+        SummaryField {
+            key,
+            label: None,
+            value: value.to_string(),
+        }
+    }
+
+    /// A template field that has no meaning for this address family, kept so
+    /// both families accept the same templates.
+    pub fn not_applicable(key: &'static str) -> Self {
+        SummaryField::hidden(key, "N/A")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field(String),
+}
+
+/// Output template with `{field}` placeholders; `{{` and `}}` are literal braces.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut field = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => field.push(c),
+                            None => return Err(Error::TemplateParse(template.to_string())),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(field.trim().to_string()));
+                }
+                '}' => return Err(Error::TemplateParse(template.to_string())),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(Template { parts })
+    }
+
+    pub fn render(&self, fields: &[SummaryField]) -> Result<String> {
+        let mut output = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => output.push_str(literal),
+                Part::Field(key) => {
+                    let field = fields
+                        .iter()
+                        .find(|field| field.key == key)
+                        .ok_or_else(|| Error::UnknownTemplateField(key.clone()))?;
+
+                    output.push_str(&field.value);
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn renders_fields_and_escaped_braces() {
+        let template = Template::parse("{{{network}/{ prefix }}}").unwrap();
+        let fields = [
+            SummaryField::new("network", "Network address", "10.0.0.0"),
+            SummaryField::hidden("prefix", 8),
+        ];
+
+        assert_eq!(template.render(&fields).unwrap(), "{10.0.0.0/8}");
+    }
+
+    #[test]
+    fn reports_bad_templates() {
+        assert!(matches!(
+            Template::parse("{network"),
+            Err(Error::TemplateParse(_))
+        ));
+        assert!(matches!(
+            Template::parse("network}"),
+            Err(Error::TemplateParse(_))
+        ));
+
+        let e = Template::parse("{nope}").unwrap().render(&[]).unwrap_err();
+
+        assert!(matches!(e, Error::UnknownTemplateField(key) if key == "nope"));
+    }
+}
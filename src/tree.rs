@@ -0,0 +1,314 @@
+use clap::{Args, ValueEnum};
+use ipnet::IpNet;
+use std::io::Write;
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::NetworkDisplay;
+use crate::parse::parse_network;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum TreeFormat {
+    #[default]
+    Unicode,
+    Ascii,
+    Dot,
+    Mermaid,
+}
+
+#[derive(Args)]
+pub struct TreeArgs {
+    /// Network at the root of the tree
+    #[arg(value_parser = parse_network)]
+    network: IpNet,
+    /// Allocated prefixes inside the network, which may nest
+    #[arg(value_parser = parse_network)]
+    allocations: Vec<IpNet>,
+    /// How to draw the tree
+    #[arg(short, long, value_enum, default_value_t)]
+    format: TreeFormat,
+}
+
+/// A network in the plan; free nodes are the gaps between allocations.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Node<N> {
+    pub network: N,
+    pub free: bool,
+    pub children: Vec<Node<N>>,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &TreeArgs) -> Result<()> {
+    let lines = match args.network {
+        IpNet::V4(ipv4) => {
+            let allocations = family(args, |net| match net {
+                IpNet::V4(net) => Some(*net),
+                _ => None,
+            })?;
+
+            render(&build(ipv4, &allocations)?, args.format)
+        }
+        IpNet::V6(ipv6) => {
+            let allocations = family(args, |net| match net {
+                IpNet::V6(net) => Some(*net),
+                _ => None,
+            })?;
+
+            render(&build(ipv6, &allocations)?, args.format)
+        }
+    };
+
+    for line in lines {
+        ctx.writeln(line)?;
+    }
+
+    Ok(())
+}
+
+/// Allocations of the root's address family, rejecting any of the other one.
+fn family<N>(args: &TreeArgs, select: impl Fn(&IpNet) -> Option<N>) -> Result<Vec<N>> {
+    args.allocations
+        .iter()
+        .map(|net| {
+            select(net)
+                .ok_or_else(|| Error::NotInNetwork(net.to_string(), args.network.to_string()))
+        })
+        .collect()
+}
+
+/// Nests each allocation under the smallest other allocation holding it, and
+/// fills the rest of every level with free blocks.
+pub fn build<N>(network: N, allocations: &[N]) -> Result<Node<N>>
+where
+    N: NetworkDisplay + Copy + PartialEq,
+{
+    let root = N::from_addr_prefix(
+        network.primitive_to_addr(network.network_primitive()),
+        network.prefix_len(),
+    )?;
+    let mut allocations: Vec<N> = allocations
+        .iter()
+        .map(|net| {
+            N::from_addr_prefix(
+                net.primitive_to_addr(net.network_primitive()),
+                net.prefix_len(),
+            )
+        })
+        .collect::<Result<_>>()?;
+
+    if let Some(outside) = allocations.iter().find(|net| !root.covers(net)) {
+        return Err(Error::NotInNetwork(outside.to_string(), root.to_string()));
+    }
+
+    allocations.sort_by_key(|net| (net.network_primitive(), net.prefix_len()));
+    allocations.dedup();
+    allocations.retain(|net| *net != root);
+
+    build_node(root, &allocations)
+}
+
+fn build_node<N>(network: N, allocations: &[N]) -> Result<Node<N>>
+where
+    N: NetworkDisplay + Copy + PartialEq,
+{
+    let inside: Vec<N> = allocations
+        .iter()
+        .filter(|net| network.covers(net) && **net != network)
+        .copied()
+        .collect();
+    let direct: Vec<N> = inside
+        .iter()
+        .filter(|net| {
+            !inside
+                .iter()
+                .any(|other| other != *net && other.covers(net))
+        })
+        .copied()
+        .collect();
+
+    let mut children = direct
+        .iter()
+        .map(|child| build_node(*child, &inside))
+        .collect::<Result<Vec<_>>>()?;
+
+    if !direct.is_empty() {
+        for gap in network.free_blocks(&direct)? {
+            children.push(Node {
+                network: gap,
+                free: true,
+                children: Vec::new(),
+            });
+        }
+    }
+
+    children.sort_by_key(|child| child.network.network_primitive());
+
+    Ok(Node {
+        network,
+        free: false,
+        children,
+    })
+}
+
+pub fn render<N: NetworkDisplay>(root: &Node<N>, format: TreeFormat) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    match format {
+        TreeFormat::Unicode => {
+            lines.push(root.network.to_string());
+            text_children(root, "", ["├── ", "└── ", "│   "], &mut lines);
+        }
+        TreeFormat::Ascii => {
+            lines.push(root.network.to_string());
+            text_children(root, "", ["|-- ", "`-- ", "|   "], &mut lines);
+        }
+        TreeFormat::Dot => {
+            lines.push("digraph iprs {".to_string());
+            lines.push("  node [shape=box];".to_string());
+            lines.push(format!("  \"{}\";", root.network));
+            dot_children(root, &mut lines);
+            lines.push("}".to_string());
+        }
+        TreeFormat::Mermaid => {
+            lines.push("graph TD".to_string());
+            lines.push(format!("  n0[\"{}\"]", root.network));
+            mermaid_children(root, 0, &mut 0, &mut lines);
+            lines.push("  classDef free stroke-dasharray: 5 5".to_string());
+        }
+    }
+
+    lines
+}
+
+fn label<N: NetworkDisplay>(node: &Node<N>) -> String {
+    if node.free {
+        format!("{} (free)", node.network)
+    } else {
+        node.network.to_string()
+    }
+}
+
+/// `branches` holds the prefixes for a middle child, the last child, and the
+/// lines below a middle child.
+fn text_children<N: NetworkDisplay>(
+    node: &Node<N>,
+    indent: &str,
+    branches: [&str; 3],
+    lines: &mut Vec<String>,
+) {
+    let [middle, last, through] = branches;
+
+    for (i, child) in node.children.iter().enumerate() {
+        let is_last = i + 1 == node.children.len();
+
+        lines.push(format!(
+            "{indent}{}{}",
+            if is_last { last } else { middle },
+            label(child)
+        ));
+
+        let indent = format!("{indent}{}", if is_last { "    " } else { through });
+        text_children(child, &indent, branches, lines);
+    }
+}
+
+fn dot_children<N: NetworkDisplay>(node: &Node<N>, lines: &mut Vec<String>) {
+    for child in &node.children {
+        if child.free {
+            lines.push(format!(
+                "  \"{}\" [style=dashed, label=\"{}\\nfree\"];",
+                child.network, child.network
+            ));
+        }
+
+        lines.push(format!("  \"{}\" -> \"{}\";", node.network, child.network));
+        dot_children(child, lines);
+    }
+}
+
+/// Mermaid ids can't contain `/` or `:`, so nodes are numbered in order.
+fn mermaid_children<N: NetworkDisplay>(
+    node: &Node<N>,
+    id: usize,
+    next_id: &mut usize,
+    lines: &mut Vec<String>,
+) {
+    for child in &node.children {
+        *next_id += 1;
+        let child_id = *next_id;
+
+        lines.push(format!(
+            "  n{id} --> n{child_id}[\"{}\"]{}",
+            label(child),
+            if child.free { ":::free" } else { "" }
+        ));
+        mermaid_children(child, child_id, next_id, lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use ipnet::Ipv4Net;
+
+    use pretty_assertions::assert_eq;
+
+    fn plan() -> Node<Ipv4Net> {
+        let allocations: Vec<_> = ["10.0.4.0/24", "10.0.0.0/24", "10.0.4.0/22"]
+            .iter()
+            .map(|net| Ipv4Net::from_str(net).unwrap())
+            .collect();
+
+        build(Ipv4Net::from_str("10.0.0.0/20").unwrap(), &allocations).unwrap()
+    }
+
+    #[test]
+    fn draws_nested_allocations_with_gaps() {
+        assert_eq!(
+            render(&plan(), TreeFormat::Unicode),
+            [
+                "10.0.0.0/20",
+                "├── 10.0.0.0/24",
+                "├── 10.0.1.0/24 (free)",
+                "├── 10.0.2.0/23 (free)",
+                "├── 10.0.4.0/22",
+                "│   ├── 10.0.4.0/24",
+                "│   ├── 10.0.5.0/24 (free)",
+                "│   └── 10.0.6.0/23 (free)",
+                "└── 10.0.8.0/21 (free)",
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_mermaid_with_numbered_nodes() {
+        let root = build(
+            Ipv4Net::from_str("10.0.0.0/23").unwrap(),
+            &[Ipv4Net::from_str("10.0.1.0/24").unwrap()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            render(&root, TreeFormat::Mermaid),
+            [
+                "graph TD",
+                "  n0[\"10.0.0.0/23\"]",
+                "  n0 --> n1[\"10.0.0.0/24 (free)\"]:::free",
+                "  n0 --> n2[\"10.0.1.0/24\"]",
+                "  classDef free stroke-dasharray: 5 5",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_allocations_outside_the_network() {
+        let e = build(
+            Ipv4Net::from_str("10.0.0.0/24").unwrap(),
+            &[Ipv4Net::from_str("10.0.1.0/24").unwrap()],
+        )
+        .unwrap_err();
+
+        assert!(matches!(e, Error::NotInNetwork(_, _)));
+    }
+}
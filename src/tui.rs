@@ -0,0 +1,376 @@
+use clap::Args;
+use ipnet::IpNet;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::terminal;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashSet;
+use std::io::Write;
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::{NetworkCore, NetworkDisplay};
+use crate::parse::parse_network;
+use crate::template::SummaryField;
+
+const HELP: &str = "↑/↓ move  → split  ← collapse  space allocate  q quit";
+
+#[derive(Args)]
+pub struct TuiArgs {
+    /// Network to start exploring from
+    #[arg(value_parser = parse_network)]
+    network: IpNet,
+    /// Prefix length added with each level of splitting
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=8))]
+    step: u8,
+}
+
+/// Opens the explorer, then prints the subnets marked as allocated.
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &TuiArgs) -> Result<()> {
+    let mut app = App::new(args.network.trunc(), args.step);
+    let mut terminal = ratatui::try_init().map_err(|e| {
+        // Setup may have failed after raw mode was already enabled
+        if terminal::is_raw_mode_enabled().unwrap_or(false) {
+            ratatui::restore();
+        }
+
+        Error::Terminal(e)
+    })?;
+
+    {
+        let _restore = RestoreTerminal;
+        app.run(&mut terminal)?;
+    }
+
+    for allocation in app.allocations() {
+        ctx.writeln(allocation)?;
+    }
+
+    Ok(())
+}
+
+/// Restores the terminal when dropped, so it happens on every way out of the
+/// event loop, including errors.
+struct RestoreTerminal;
+
+impl Drop for RestoreTerminal {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+/// A visible row of the tree.
+struct Row {
+    network: IpNet,
+    depth: usize,
+}
+
+struct App {
+    root: IpNet,
+    step: u8,
+    expanded: HashSet<IpNet>,
+    allocated: HashSet<IpNet>,
+    list: ListState,
+    status: String,
+    quit: bool,
+}
+
+impl App {
+    fn new(root: IpNet, step: u8) -> Self {
+        App {
+            root,
+            step,
+            expanded: HashSet::new(),
+            allocated: HashSet::new(),
+            list: ListState::default().with_selected(Some(0)),
+            status: HELP.to_string(),
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key.code);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Allocated subnets in address order.
+    fn allocations(&self) -> Vec<IpNet> {
+        let mut allocations: Vec<_> = self.allocated.iter().copied().collect();
+        allocations.sort();
+
+        allocations
+    }
+
+    /// The tree flattened into the rows currently on screen.
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut stack = vec![Row {
+            network: self.root,
+            depth: 0,
+        }];
+
+        while let Some(row) = stack.pop() {
+            if self.expanded.contains(&row.network) {
+                if let Ok(children) = self.children(&row.network) {
+                    stack.extend(children.into_iter().rev().map(|network| Row {
+                        network,
+                        depth: row.depth + 1,
+                    }));
+                }
+            }
+
+            rows.push(row);
+        }
+
+        rows
+    }
+
+    fn children(&self, network: &IpNet) -> Result<Vec<IpNet>> {
+        let max_prefix_len = network.max_prefix_len();
+
+        if network.prefix_len() == max_prefix_len {
+            return Err(Error::SplitTooBig(
+                max_prefix_len,
+                network.prefix_len() + self.step,
+            ));
+        }
+
+        let prefix_len = (network.prefix_len() + self.step).min(max_prefix_len);
+
+        Ok(match network {
+            IpNet::V4(ipv4) => NetworkCore::subnets(ipv4, prefix_len)?
+                .map(IpNet::V4)
+                .collect(),
+            IpNet::V6(ipv6) => NetworkCore::subnets(ipv6, prefix_len)?
+                .map(IpNet::V6)
+                .collect(),
+        })
+    }
+
+    fn selected(&self) -> Option<IpNet> {
+        let rows = self.rows();
+
+        self.list
+            .selected()
+            .and_then(|i| rows.get(i))
+            .map(|row| row.network)
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let Some(selected) = self.selected() else {
+            return;
+        };
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => {
+                let last = self.rows().len().saturating_sub(1);
+                let i = self.list.selected().unwrap_or(0);
+
+                self.list.select(Some((i + 1).min(last)));
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
+                match self.children(&selected) {
+                    Ok(_) => {
+                        self.expanded.insert(selected);
+                    }
+                    Err(e) => self.status = e.to_string(),
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.collapse(selected),
+            KeyCode::Char(' ') | KeyCode::Char('a') => self.toggle_allocated(selected),
+            _ => {}
+        }
+    }
+
+    /// Collapsing a leaf jumps to its parent row instead.
+    fn collapse(&mut self, network: IpNet) {
+        if self.expanded.remove(&network) {
+            return;
+        }
+
+        let rows = self.rows();
+        let Some(i) = self.list.selected() else {
+            return;
+        };
+
+        if let Some(parent) = rows[..i]
+            .iter()
+            .rposition(|row| row.network.contains(&network))
+        {
+            self.list.select(Some(parent));
+        }
+    }
+
+    fn toggle_allocated(&mut self, network: IpNet) {
+        if self.allocated.remove(&network) {
+            self.status = format!("Released {network}");
+        } else if let Some(other) = self
+            .allocations()
+            .into_iter()
+            .find(|other| other.contains(&network) || network.contains(other))
+        {
+            self.status = format!("{network} overlaps the allocation {other}");
+        } else {
+            self.allocated.insert(network);
+            self.status = format!("Allocated {network}");
+        }
+    }
+
+    fn label(&self, row: &Row) -> String {
+        let marker = if self.expanded.contains(&row.network) {
+            "▾"
+        } else {
+            "▸"
+        };
+        let state = if self.allocated.contains(&row.network) {
+            " [allocated]"
+        } else if self
+            .allocated
+            .iter()
+            .any(|other| other.contains(&row.network))
+        {
+            " [in allocation]"
+        } else if self
+            .allocated
+            .iter()
+            .any(|other| row.network.contains(other))
+        {
+            " [partly allocated]"
+        } else {
+            ""
+        };
+
+        format!("{}{marker} {}{state}", "  ".repeat(row.depth), row.network)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [tree, summary] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(main);
+
+        let items: Vec<_> = self
+            .rows()
+            .iter()
+            .map(|row| ListItem::new(self.label(row)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" {} ", self.root)))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(list, tree, &mut self.list);
+
+        let lines: Vec<Line> = self
+            .selected()
+            .map(|network| summary_fields(&network))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|field| {
+                let label = field.label?;
+
+                Some(Line::from(format!("{label: <24}- {}", field.value)))
+            })
+            .collect();
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Summary ")),
+            summary,
+        );
+        frame.render_widget(Line::from(self.status.as_str()), status);
+    }
+}
+
+fn summary_fields(network: &IpNet) -> Vec<SummaryField> {
+    match network {
+        IpNet::V4(ipv4) => ipv4.summary_fields(),
+        IpNet::V6(ipv6) => ipv6.summary_fields(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use pretty_assertions::assert_eq;
+
+    fn render(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn drills_down_and_shows_the_selected_summary() {
+        let mut app = App::new(IpNet::from_str("10.0.0.0/24").unwrap(), 1);
+
+        app.handle_key(KeyCode::Right);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Right);
+
+        let labels: Vec<_> = app.rows().iter().map(|row| app.label(row)).collect();
+
+        assert_eq!(
+            labels,
+            [
+                "▾ 10.0.0.0/24",
+                "  ▸ 10.0.0.0/25",
+                "  ▾ 10.0.0.128/25",
+                "    ▸ 10.0.0.128/26",
+                "    ▸ 10.0.0.192/26",
+            ]
+        );
+
+        let screen = render(&mut app);
+
+        assert!(screen.contains("Network address         - 10.0.0.128"));
+        assert!(screen.contains("Addresses in network    - 128"));
+    }
+
+    #[test]
+    fn marks_allocations_without_overlap() {
+        let mut app = App::new(IpNet::from_str("10.0.0.0/24").unwrap(), 1);
+
+        app.handle_key(KeyCode::Right);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Char(' '));
+        app.handle_key(KeyCode::Up);
+        app.handle_key(KeyCode::Char(' '));
+
+        assert_eq!(
+            app.status,
+            "10.0.0.0/24 overlaps the allocation 10.0.0.0/25"
+        );
+        assert_eq!(app.allocations(), [IpNet::from_str("10.0.0.0/25").unwrap()]);
+        assert!(render(&mut app).contains("▾ 10.0.0.0/24 [partly allocated]"));
+    }
+}
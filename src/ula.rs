@@ -0,0 +1,148 @@
+use clap::Args;
+use ipnet::Ipv6Net;
+use sha1::{Digest, Sha1};
+use std::io::Write;
+use std::net::Ipv6Addr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::format::OutputFormat;
+use crate::interface::{traits::SummaryOptions, Interface};
+use crate::rng::{DefaultRng, NetworkRng, SeededRng};
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+const GLOBAL_ID_BITS: u32 = 40;
+
+#[derive(Args)]
+pub struct UlaArgs {
+    /// MAC address or EUI-64 to derive the Global ID from using the RFC 4193 SHA-1 algorithm
+    #[arg(long)]
+    mac: Option<String>,
+    /// Unix timestamp for the RFC 4193 algorithm, defaulting to the current time
+    #[arg(long, requires = "mac")]
+    timestamp: Option<f64>,
+    /// Seed the random Global ID so it can be reproduced
+    #[arg(long, conflicts_with = "mac")]
+    seed: Option<u64>,
+    /// Split the generated /48 into subnets of this prefix length
+    #[arg(short, long)]
+    split: Option<u8>,
+    /// Add the addresses and masks in binary to the summary
+    #[arg(short, long)]
+    binary: bool,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &UlaArgs) -> Result<()> {
+    let global_id = match (&args.mac, args.seed) {
+        (Some(mac), _) => {
+            let timestamp = match args.timestamp {
+                Some(timestamp) => timestamp,
+                None => SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs_f64(),
+            };
+
+            rfc4193_global_id(ntp_timestamp(timestamp), parse_eui64(mac)?)
+        }
+        (None, Some(seed)) => random_global_id(&mut SeededRng::new(seed)),
+        (None, None) => random_global_id(&mut DefaultRng),
+    };
+
+    let prefix = ula_prefix(global_id);
+
+    match args.split {
+        Some(split) => prefix.split(ctx, split, OutputFormat::Text),
+        None => prefix.summarize(
+            ctx,
+            SummaryOptions {
+                binary: args.binary,
+            },
+        ),
+    }
+}
+
+/// The fd00::/8 (L bit set) /48 for a 40-bit Global ID.
+pub fn ula_prefix(global_id: u64) -> Ipv6Net {
+    let global_id = u128::from(global_id) & ((1 << GLOBAL_ID_BITS) - 1);
+    let addr = (0xfd << 120) | (global_id << 80);
+
+    Ipv6Net::new(Ipv6Addr::from(addr), 48).expect("48 is a valid IPv6 prefix length")
+}
+
+pub fn random_global_id<R: NetworkRng>(rng: &mut R) -> u64 {
+    let global_id: u128 = rng.random_range(0..1 << GLOBAL_ID_BITS);
+
+    global_id as u64
+}
+
+/// Global ID per RFC 4193 section 3.2.2: the low 40 bits of SHA-1(NTP time || EUI-64).
+pub fn rfc4193_global_id(ntp_timestamp: u64, eui64: [u8; 8]) -> u64 {
+    let mut hasher = Sha1::new();
+    hasher.update(ntp_timestamp.to_be_bytes());
+    hasher.update(eui64);
+    let digest = hasher.finalize();
+
+    let mut low = [0u8; 8];
+    low[3..].copy_from_slice(&digest[15..]);
+
+    u64::from_be_bytes(low)
+}
+
+/// 64-bit NTP format: seconds since 1900 in the high half, binary fraction in the low half.
+///
+/// The seconds field wraps into the next NTP era in 2036, as the format does.
+pub fn ntp_timestamp(unix_seconds: f64) -> u64 {
+    let seconds = (unix_seconds.trunc() as u64 + NTP_UNIX_OFFSET) & u64::from(u32::MAX);
+    let fraction = (unix_seconds.fract() * (1u64 << 32) as f64) as u64;
+
+    (seconds << 32) | fraction
+}
+
+/// Parses an EUI-64, or a MAC-48 converted to a modified EUI-64 (RFC 4291 appendix A).
+pub fn parse_eui64(input: &str) -> Result<[u8; 8]> {
+    let bytes = input
+        .split([':', '-'])
+        .map(|byte| u8::from_str_radix(byte, 16))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| Error::MacParse(input.to_string()))?;
+
+    match bytes.as_slice() {
+        [a, b, c, d, e, f] => Ok([a ^ 0x02, *b, *c, 0xff, 0xfe, *d, *e, *f]),
+        [a, b, c, d, e, f, g, h] => Ok([*a, *b, *c, *d, *e, *f, *g, *h]),
+        _ => Err(Error::MacParse(input.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn converts_mac_to_modified_eui64() {
+        let eui64 = parse_eui64("00:11:22:33:44:55").unwrap();
+
+        assert_eq!(eui64, [0x02, 0x11, 0x22, 0xff, 0xfe, 0x33, 0x44, 0x55]);
+        assert!(matches!(parse_eui64("00:11:22"), Err(Error::MacParse(_))));
+    }
+
+    #[test]
+    fn derives_global_id_per_rfc4193() {
+        let eui64 = parse_eui64("00-11-22-33-44-55").unwrap();
+        let global_id = rfc4193_global_id(ntp_timestamp(1_700_000_000.0), eui64);
+
+        assert_eq!(ula_prefix(global_id).to_string(), "fd8e:493:e0f0::/48");
+    }
+
+    #[test]
+    fn random_prefix_sets_the_local_bit() {
+        let prefix = ula_prefix(random_global_id(&mut SeededRng::new(1)));
+
+        assert_eq!(prefix.prefix_len(), 48);
+        assert_eq!(prefix.addr().segments()[0] >> 8, 0xfd);
+    }
+}
@@ -0,0 +1,261 @@
+use clap::{Args, ValueEnum};
+use ipnet::Ipv4Net;
+use std::io::Write;
+use std::net::Ipv4Addr;
+
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::NetworkCore;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Provider {
+    Aws,
+    Azure,
+    Gcp,
+}
+
+impl Provider {
+    /// Addresses reserved after the network address and before the broadcast address.
+    ///
+    /// AWS and Azure keep the next three for the router, DNS and future use; GCP
+    /// keeps the gateway and the second-to-last address.
+    fn reserved(self) -> (u64, u64) {
+        match self {
+            Provider::Aws | Provider::Azure => (3, 0),
+            Provider::Gcp => (1, 1),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Provider::Aws => "aws",
+            Provider::Azure => "azure",
+            Provider::Gcp => "gcp",
+        }
+    }
+
+    /// Longest prefix the provider accepts for a subnet.
+    fn max_prefix_len(self) -> u8 {
+        match self {
+            Provider::Aws => 28,
+            Provider::Azure | Provider::Gcp => 29,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum VpcFormat {
+    #[default]
+    Text,
+    Tfvars,
+}
+
+#[derive(Args)]
+pub struct VpcArgs {
+    /// CIDR of the whole VPC
+    vpc: Ipv4Net,
+    /// Cloud provider, which decides the reserved addresses per subnet
+    #[arg(short, long, value_enum)]
+    provider: Provider,
+    /// Number of availability zones
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=26))]
+    azs: u8,
+    /// Subnet tiers, each getting one subnet per zone
+    #[arg(long, value_delimiter = ',', default_value = "public,private,data")]
+    tiers: Vec<String>,
+    /// Prefix length of each subnet, defaulting to the largest that fits
+    #[arg(short, long)]
+    size: Option<u8>,
+    #[arg(short, long, value_enum, default_value_t)]
+    format: VpcFormat,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Subnet {
+    tier: String,
+    zone: char,
+    network: Ipv4Net,
+    first_usable: Ipv4Addr,
+    last_usable: Ipv4Addr,
+    usable: u32,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &VpcArgs) -> Result<()> {
+    let vpc = args.vpc.trunc();
+    let subnets = plan(&vpc, args)?;
+
+    match args.format {
+        VpcFormat::Text => {
+            let name_width = subnets
+                .iter()
+                .map(|subnet| subnet.tier.len() + 2)
+                .max()
+                .unwrap_or(0);
+
+            ctx.writeln(format!("-[ipv4 : {vpc}] - 0\n"))?;
+            ctx.writeln(format!("[VPC layout ({})]", args.provider.name()))?;
+
+            for subnet in &subnets {
+                ctx.writeln(format!(
+                    "{:<name_width$} - {:<18} - {} - {} ({} usable)",
+                    format!("{}-{}", subnet.tier, subnet.zone),
+                    subnet.network.to_string(),
+                    subnet.first_usable,
+                    subnet.last_usable,
+                    subnet.usable
+                ))?;
+            }
+        }
+        VpcFormat::Tfvars => {
+            ctx.writeln(format!("vpc_cidr = \"{vpc}\""))?;
+
+            for tier in &args.tiers {
+                ctx.writeln(format!("\n{}_subnets = [", tier.replace('-', "_")))?;
+
+                for subnet in subnets.iter().filter(|subnet| &subnet.tier == tier) {
+                    ctx.writeln(format!("  \"{}\",", subnet.network))?;
+                }
+
+                ctx.writeln("]".to_string())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of bits needed to give each of `count` blocks its own index.
+fn index_bits(count: usize) -> u8 {
+    count.next_power_of_two().trailing_zeros() as u8
+}
+
+/// Splits the VPC into one block per tier, then each tier block into one
+/// subnet per zone, so a tier's subnets stay contiguous.
+fn plan(vpc: &Ipv4Net, args: &VpcArgs) -> Result<Vec<Subnet>> {
+    let zones = usize::from(args.azs);
+    let zone_bits = index_bits(zones);
+    let needed = vpc.prefix_len() + index_bits(args.tiers.len()) + zone_bits;
+    let prefix_len = args.size.unwrap_or(needed);
+
+    if prefix_len < needed {
+        return Err(Error::SplitSmallerThanPrefixLen(prefix_len, needed));
+    }
+    if prefix_len > args.provider.max_prefix_len() {
+        return Err(Error::SplitTooBig(
+            args.provider.max_prefix_len(),
+            prefix_len,
+        ));
+    }
+
+    let tier_blocks: Vec<Ipv4Net> = vpc.subnets(prefix_len - zone_bits)?.collect();
+    let (after_network, before_broadcast) = args.provider.reserved();
+    let mut subnets = Vec::new();
+
+    for (tier, block) in args.tiers.iter().zip(tier_blocks) {
+        for (zone, network) in ('a'..).zip(block.subnets(prefix_len)?.take(zones)) {
+            let hosts = network.usable_hosts().skip_hosts(after_network);
+            let (Some(first_usable), Some(last_usable)) = (
+                hosts.nth_host(0),
+                hosts.nth_host(-1 - i128::from(before_broadcast)),
+            ) else {
+                return Err(Error::NoRoomForReservations(
+                    network.to_string(),
+                    args.provider.name(),
+                ));
+            };
+
+            subnets.push(Subnet {
+                tier: tier.clone(),
+                zone,
+                network,
+                first_usable,
+                last_usable,
+                usable: u32::from(last_usable) - u32::from(first_usable) + 1,
+            });
+        }
+    }
+
+    Ok(subnets)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+
+    use pretty_assertions::assert_eq;
+
+    fn args(provider: Provider, format: VpcFormat) -> VpcArgs {
+        VpcArgs {
+            vpc: Ipv4Net::from_str("10.0.0.0/16").unwrap(),
+            provider,
+            azs: 3,
+            tiers: vec!["public".to_string(), "private".to_string()],
+            size: None,
+            format,
+        }
+    }
+
+    #[test]
+    fn lays_out_tiers_per_zone_with_aws_reservations() {
+        let mut ctx = create_test_ctx();
+
+        run(&mut ctx, &args(Provider::Aws, VpcFormat::Text)).unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "-[ipv4 : 10.0.0.0/16] - 0
+
+[VPC layout (aws)]
+public-a  - 10.0.0.0/19        - 10.0.0.4 - 10.0.31.254 (8187 usable)
+public-b  - 10.0.32.0/19       - 10.0.32.4 - 10.0.63.254 (8187 usable)
+public-c  - 10.0.64.0/19       - 10.0.64.4 - 10.0.95.254 (8187 usable)
+private-a - 10.0.128.0/19      - 10.0.128.4 - 10.0.159.254 (8187 usable)
+private-b - 10.0.160.0/19      - 10.0.160.4 - 10.0.191.254 (8187 usable)
+private-c - 10.0.192.0/19      - 10.0.192.4 - 10.0.223.254 (8187 usable)
+"
+        );
+    }
+
+    #[test]
+    fn reserves_four_addresses_on_gcp() {
+        let args = VpcArgs {
+            size: Some(24),
+            ..args(Provider::Gcp, VpcFormat::Text)
+        };
+        let subnets = plan(&args.vpc, &args).unwrap();
+
+        assert_eq!(subnets[0].first_usable, Ipv4Addr::new(10, 0, 0, 2));
+        assert_eq!(subnets[0].last_usable, Ipv4Addr::new(10, 0, 0, 253));
+        assert_eq!(subnets[0].usable, 252);
+    }
+
+    #[test]
+    fn writes_terraform_variables() {
+        let mut ctx = create_test_ctx();
+        let args = VpcArgs {
+            azs: 2,
+            ..args(Provider::Azure, VpcFormat::Tfvars)
+        };
+
+        run(&mut ctx, &args).unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "vpc_cidr = \"10.0.0.0/16\"
+
+public_subnets = [
+  \"10.0.0.0/18\",
+  \"10.0.64.0/18\",
+]
+
+private_subnets = [
+  \"10.0.128.0/18\",
+  \"10.0.192.0/18\",
+]
+"
+        );
+    }
+}
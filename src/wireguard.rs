@@ -0,0 +1,299 @@
+use clap::Args;
+use ipnet::IpNet;
+use std::io::Write;
+
+use crate::allocate::allocate;
+use crate::context::Ctx;
+use crate::error::{Error, Result};
+use crate::interface::traits::{NetworkDisplay, NetworkPrimitive};
+use crate::parse::parse_network;
+use crate::rng::{HashRng, RandomRangeGenerator};
+
+#[derive(Args)]
+pub struct WireguardArgs {
+    /// Tunnel network, or one IPv4 and one IPv6 network for a dual-stack tunnel
+    #[arg(required = true, value_parser = parse_network)]
+    networks: Vec<IpNet>,
+    /// Names of the peers. Each name hashes to the same address whatever the
+    /// order of the list. When two names hash to the same address the one
+    /// sorting first keeps it, so adding a peer can move an existing peer it
+    /// collides with.
+    #[arg(short, long = "peer", value_delimiter = ',', required = true)]
+    peers: Vec<String>,
+    /// Public endpoint of the server, e.g. vpn.example.com:51820
+    #[arg(short, long)]
+    endpoint: Option<String>,
+    /// UDP port the server listens on
+    #[arg(long, default_value_t = 51820)]
+    listen_port: u16,
+    /// Collapse the addresses each peer routes into the fewest prefixes
+    #[arg(short, long)]
+    aggregate: bool,
+}
+
+/// The addresses of the server and every peer in one tunnel network.
+struct Tunnel {
+    network: IpNet,
+    server: IpNet,
+    peers: Vec<IpNet>,
+}
+
+pub fn run<W: Write, E: Write>(ctx: &mut Ctx<W, E>, args: &WireguardArgs) -> Result<()> {
+    let mut tunnels: Vec<Tunnel> = Vec::new();
+
+    for network in &args.networks {
+        let network = network.trunc();
+
+        if tunnels
+            .iter()
+            .any(|tunnel| tunnel.network.addr().is_ipv4() == network.addr().is_ipv4())
+        {
+            return Err(Error::DuplicateFamily(network.to_string()));
+        }
+
+        let (server, peers) = match network {
+            IpNet::V4(ipv4) => {
+                let (server, peers) = assign(&ipv4, &args.peers)?;
+
+                (
+                    IpNet::V4(server),
+                    peers.into_iter().map(IpNet::V4).collect(),
+                )
+            }
+            IpNet::V6(ipv6) => {
+                let (server, peers) = assign(&ipv6, &args.peers)?;
+
+                (
+                    IpNet::V6(server),
+                    peers.into_iter().map(IpNet::V6).collect(),
+                )
+            }
+        };
+
+        tunnels.push(Tunnel {
+            network,
+            server,
+            peers,
+        });
+    }
+
+    let server_address = join(
+        tunnels
+            .iter()
+            .map(|tunnel| format!("{}/{}", tunnel.server.addr(), tunnel.network.prefix_len())),
+    );
+
+    ctx.writeln("# Server")?;
+    ctx.writeln("[Interface]")?;
+    ctx.writeln(format!("Address = {server_address}"))?;
+    ctx.writeln(format!("ListenPort = {}", args.listen_port))?;
+    ctx.writeln("PrivateKey = <server private key>")?;
+
+    for (i, peer) in args.peers.iter().enumerate() {
+        ctx.writeln(format!("\n[Peer]\n# {peer}"))?;
+        ctx.writeln(format!("PublicKey = <{peer} public key>"))?;
+        ctx.writeln(format!(
+            "AllowedIPs = {}",
+            join(tunnels.iter().map(|tunnel| tunnel.peers[i]))
+        ))?;
+    }
+
+    for (i, peer) in args.peers.iter().enumerate() {
+        let mut routed: Vec<IpNet> = Vec::new();
+
+        for tunnel in &tunnels {
+            routed.push(tunnel.server);
+            routed.extend(
+                tunnel
+                    .peers
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, net)| *net),
+            );
+        }
+
+        if args.aggregate {
+            routed = IpNet::aggregate(&routed);
+        }
+
+        ctx.writeln(format!("\n# {peer}"))?;
+        ctx.writeln("[Interface]")?;
+        ctx.writeln(format!(
+            "Address = {}",
+            join(tunnels.iter().map(|tunnel| tunnel.peers[i]))
+        ))?;
+        ctx.writeln(format!("PrivateKey = <{peer} private key>"))?;
+        ctx.writeln("\n[Peer]")?;
+        ctx.writeln("PublicKey = <server public key>")?;
+        ctx.writeln(format!("AllowedIPs = {}", join(routed.iter())))?;
+
+        if let Some(endpoint) = &args.endpoint {
+            ctx.writeln(format!("Endpoint = {endpoint}"))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn join<D: std::fmt::Display>(items: impl Iterator<Item = D>) -> String {
+    items
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Gives the server the first host of `network` and hashes each peer to one
+/// of the rest, returning single-address networks in the order of `peers`.
+///
+/// Peers are placed in name order, so collisions resolve the same way however
+/// the list is ordered.
+fn assign<N>(network: &N, peers: &[String]) -> Result<(N, Vec<N>)>
+where
+    N: NetworkDisplay + Copy,
+    HashRng: RandomRangeGenerator<N::Primitive>,
+{
+    let host =
+        |primitive| N::from_addr_prefix(network.primitive_to_addr(primitive), N::Primitive::BITS);
+    let mut hosts = network.usable_hosts();
+
+    // The network address itself is usable in IPv6 but is the subnet-router anycast
+    if hosts
+        .bounds()
+        .is_some_and(|(first, _)| first == network.network_primitive())
+    {
+        hosts = hosts.skip_hosts(1);
+    }
+
+    let Some((first, last)) = hosts.bounds() else {
+        return Err(Error::NoFreeHosts(network.to_string()));
+    };
+
+    let mut avoid = vec![host(first)?];
+
+    if first != network.network_primitive() {
+        avoid.push(host(network.network_primitive())?);
+    }
+    if last != network.addr_to_primitive(network.broadcast()) {
+        avoid.push(host(network.addr_to_primitive(network.broadcast()))?);
+    }
+
+    let mut sorted = peers.to_vec();
+    sorted.sort();
+
+    let assigned = allocate(network, N::Primitive::BITS, &sorted, &avoid)?;
+    let peers = peers
+        .iter()
+        .map(|peer| {
+            assigned[sorted
+                .binary_search(peer)
+                .expect("every peer is in the sorted list")]
+        })
+        .collect();
+
+    Ok((avoid[0], peers))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::context::test_util::{create_test_ctx, get_output_as_string};
+    use ipnet::Ipv4Net;
+
+    use pretty_assertions::assert_eq;
+
+    fn peers(peers: &[&str]) -> Vec<String> {
+        peers.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn peers_keep_their_addresses() {
+        let network = Ipv4Net::from_str("10.8.0.0/24").unwrap();
+
+        let (server, first) = assign(&network, &peers(&["alice", "bob"])).unwrap();
+        let (_, second) = assign(&network, &peers(&["carol", "bob"])).unwrap();
+
+        assert_eq!(server.to_string(), "10.8.0.1/32");
+        assert_eq!(second[1], first[1]);
+    }
+
+    #[test]
+    fn never_assigns_reserved_addresses() {
+        let network = Ipv4Net::from_str("10.8.0.0/29").unwrap();
+
+        let (_, assigned) = assign(&network, &peers(&["a", "b", "c", "d", "e"])).unwrap();
+        let mut assigned: Vec<String> = assigned.iter().map(|net| net.to_string()).collect();
+        assigned.sort();
+
+        assert_eq!(
+            assigned,
+            [
+                "10.8.0.2/32",
+                "10.8.0.3/32",
+                "10.8.0.4/32",
+                "10.8.0.5/32",
+                "10.8.0.6/32"
+            ]
+        );
+        assert!(assign(&network, &peers(&["a", "b", "c", "d", "e", "f"])).is_err());
+    }
+
+    #[test]
+    fn collisions_resolve_the_same_in_any_order() {
+        let network = Ipv4Net::from_str("10.8.0.0/29").unwrap();
+
+        // Both names hash to 10.8.0.5 first
+        let (_, alone) = assign(&network, &peers(&["q"])).unwrap();
+        let (_, forward) = assign(&network, &peers(&["a", "q"])).unwrap();
+        let (_, reverse) = assign(&network, &peers(&["q", "a"])).unwrap();
+
+        assert_eq!(alone[0].to_string(), "10.8.0.5/32");
+        assert_eq!(forward[0].to_string(), "10.8.0.5/32");
+        assert_ne!(forward[1], forward[0]);
+        assert_eq!(reverse, [forward[1], forward[0]]);
+    }
+
+    #[test]
+    fn writes_dual_stack_configs() {
+        let mut ctx = create_test_ctx();
+        let args = WireguardArgs {
+            networks: vec![
+                IpNet::from_str("10.8.0.0/30").unwrap(),
+                IpNet::from_str("fd00:8::/126").unwrap(),
+            ],
+            peers: peers(&["laptop"]),
+            endpoint: Some("vpn.example.com:51820".to_string()),
+            listen_port: 51820,
+            aggregate: false,
+        };
+
+        run(&mut ctx, &args).unwrap();
+
+        assert_eq!(
+            get_output_as_string(&ctx),
+            "# Server
+[Interface]
+Address = 10.8.0.1/30, fd00:8::1/126
+ListenPort = 51820
+PrivateKey = <server private key>
+
+[Peer]
+# laptop
+PublicKey = <laptop public key>
+AllowedIPs = 10.8.0.2/32, fd00:8::2/128
+
+# laptop
+[Interface]
+Address = 10.8.0.2/32, fd00:8::2/128
+PrivateKey = <laptop private key>
+
+[Peer]
+PublicKey = <server public key>
+AllowedIPs = 10.8.0.1/32, fd00:8::1/128
+Endpoint = vpn.example.com:51820
+"
+        );
+    }
+}